@@ -0,0 +1,156 @@
+use std::f64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Activation {
+    Sigmoid,
+    Tanh,
+    Relu,
+    LeakyRelu(f64),
+    Linear,
+    Softmax
+}
+
+fn sigmoid(input: f64) -> f64 {
+    1.0 / (1.0 + f64::consts::E.powf(-input))
+}
+
+impl Activation {
+
+    pub fn get_name(&self) -> String {
+        match *self {
+            Activation::Sigmoid => String::from("sigmoid"),
+            Activation::Tanh => String::from("tanh"),
+            Activation::Relu => String::from("relu"),
+            Activation::LeakyRelu(slope) => format!("leaky_relu:{}", slope),
+            Activation::Linear => String::from("linear"),
+            Activation::Softmax => String::from("softmax")
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Activation, String> {
+        match name {
+            "sigmoid" => Ok(Activation::Sigmoid),
+            "tanh" => Ok(Activation::Tanh),
+            "relu" => Ok(Activation::Relu),
+            "linear" => Ok(Activation::Linear),
+            "softmax" => Ok(Activation::Softmax),
+            _ if name.starts_with("leaky_relu:") => match name["leaky_relu:".len()..].parse::<f64>() {
+                Ok(slope) if slope.is_finite() => Ok(Activation::LeakyRelu(slope)),
+                _ => Err(format!("Invalid leaky relu slope in \"{}\"", name))
+            },
+            _ => Err(format!("Unknown activation \"{}\"", name))
+        }
+    }
+
+    //softmax needs the whole layer, so activation always works on a complete layer
+    pub fn activate(&self, input: &[f64], output: &mut [f64]) {
+        assert_eq!(input.len(), output.len());
+
+        match *self {
+            Activation::Softmax => {
+                //shifting by the maximum keeps exp() from overflowing
+                let max = input.iter().fold(f64::MIN, | max, &e | max.max(e));
+                let mut sum = 0f64;
+                for (out, &inp) in output.iter_mut().zip(input.iter()) {
+                    *out = (inp - max).exp();
+                    sum += *out;
+                }
+                for out in output.iter_mut() {
+                    *out /= sum;
+                }
+            },
+            _ => {
+                for (out, &inp) in output.iter_mut().zip(input.iter()) {
+                    *out = self.activate_unit(inp);
+                }
+            }
+        }
+    }
+
+    fn activate_unit(&self, input: f64) -> f64 {
+        match *self {
+            Activation::Sigmoid => sigmoid(input),
+            Activation::Tanh => input.tanh(),
+            Activation::Relu => input.max(0.0),
+            Activation::LeakyRelu(slope) => match input > 0.0 {
+                true => input,
+                false => slope * input
+            },
+            Activation::Linear => input,
+            Activation::Softmax => unreachable!()
+        }
+    }
+
+    fn derivative_unit(&self, input: f64, output: f64) -> f64 {
+        match *self {
+            Activation::Sigmoid => output * (1.0 - output),
+            Activation::Tanh => 1.0 - output * output,
+            Activation::Relu => match input > 0.0 {
+                true => 1.0,
+                false => 0.0
+            },
+            Activation::LeakyRelu(slope) => match input > 0.0 {
+                true => 1.0,
+                false => slope
+            },
+            Activation::Linear => 1.0,
+            Activation::Softmax => unreachable!()
+        }
+    }
+
+    //turns the gradient with respect to the layer output into the gradient
+    //with respect to the layer input, in place
+    pub fn backpropagate(&self, input: &[f64], output: &[f64], gradient: &mut [f64]) {
+        assert_eq!(input.len(), gradient.len());
+        assert_eq!(output.len(), gradient.len());
+
+        match *self {
+            Activation::Softmax => {
+                let weighted_sum = gradient.iter()
+                    .zip(output.iter())
+                    .fold(0f64, | sum, (&grad, &out) | sum + grad * out);
+                for (grad, &out) in gradient.iter_mut().zip(output.iter()) {
+                    *grad = out * (*grad - weighted_sum);
+                }
+            },
+            _ => {
+                for unit in 0..gradient.len() {
+                    gradient[unit] *= self.derivative_unit(input[unit], output[unit]);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_softmax_backpropagate() {
+    let input = [0.5, -1.0, 2.0];
+    let mut output = [0.0; 3];
+    Activation::Softmax.activate(&input, &mut output);
+
+    assert!((output.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+    //compare against a numeric derivative of sum(output * weights)
+    let weights = [0.3, -0.7, 1.1];
+    let mut gradient = weights;
+    Activation::Softmax.backpropagate(&input, &output, &mut gradient);
+
+    for unit in 0..input.len() {
+        let mut shifted = input;
+        shifted[unit] += 1e-6;
+        let mut shifted_output = [0.0; 3];
+        Activation::Softmax.activate(&shifted, &mut shifted_output);
+
+        let numeric = (0..3).fold(0.0, | sum, i | sum + weights[i] * (shifted_output[i] - output[i])) / 1e-6;
+        assert!((numeric - gradient[unit]).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn test_activation_from_name() {
+    assert!(Activation::from_name("leaky_relu:0.01") == Ok(Activation::LeakyRelu(0.01)));
+    assert!(Activation::from_name(&Activation::LeakyRelu(0.2).get_name()) == Ok(Activation::LeakyRelu(0.2)));
+    for name in ["leaky_relu:NaN", "leaky_relu:inf", "leaky_relu:-inf", "leaky_relu:", "swish"].iter() {
+        assert!(Activation::from_name(name).is_err(), "{}", name);
+    }
+}
@@ -5,6 +5,9 @@ use allegro::*;
 use allegro_primitives::*;
 use allegro_font::*;
 
+use color;
+use renderer::Renderer;
+
 pub struct AllegroData {
     core: Core,
     display: Display,
@@ -16,8 +19,60 @@ pub struct AllegroData {
     white: Color,
 }
 
-pub trait Drawable {
-    fn draw(&self, allegro_data: &AllegroData, camera_pos: (f32, f32), scale: (f32, f32));
+pub fn to_allegro_color(color: color::Color) -> Color {
+    let (r, g, b) = color.get_rgb();
+    Color::from_rgb(r, g, b)
+}
+
+//The renderer drawing onto the current target bitmap of allegro.
+pub struct AllegroRenderer<'a> {
+    allegro_data: &'a AllegroData,
+    size: (f32, f32)
+}
+
+impl<'a> AllegroRenderer<'a> {
+    //size is the size of the target bitmap
+    pub fn new(allegro_data: &'a AllegroData, size: (f32, f32)) -> AllegroRenderer<'a> {
+        AllegroRenderer {
+            allegro_data,
+            size
+        }
+    }
+}
+
+impl<'a> Renderer for AllegroRenderer<'a> {
+
+    fn get_size(&self) -> (f32, f32) {
+        self.size
+    }
+
+    fn clear(&mut self, color: color::Color) {
+        self.allegro_data.get_core().clear_to_color(to_allegro_color(color));
+    }
+
+    fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), color: color::Color, thickness: f32) {
+        self.allegro_data.get_primitives_addon().draw_line(from.0, from.1, to.0, to.1, to_allegro_color(color), thickness);
+    }
+
+    fn draw_circle(&mut self, center: (f32, f32), radius: f32, color: color::Color, thickness: f32) {
+        self.allegro_data.get_primitives_addon().draw_circle(center.0, center.1, radius, to_allegro_color(color), thickness);
+    }
+
+    fn draw_filled_circle(&mut self, center: (f32, f32), radius: f32, color: color::Color) {
+        self.allegro_data.get_primitives_addon().draw_filled_circle(center.0, center.1, radius, to_allegro_color(color));
+    }
+
+    fn draw_pieslice(&mut self, center: (f32, f32), radius: f32, start_theta: f32, delta_theta: f32, color: color::Color, thickness: f32) {
+        self.allegro_data.get_primitives_addon().draw_pieslice(center.0, center.1, radius, start_theta, delta_theta, to_allegro_color(color), thickness);
+    }
+
+    fn draw_filled_rectangle(&mut self, from: (f32, f32), to: (f32, f32), color: color::Color) {
+        self.allegro_data.get_primitives_addon().draw_filled_rectangle(from.0, from.1, to.0, to.1, to_allegro_color(color));
+    }
+
+    fn draw_text(&mut self, pos: (f32, f32), color: color::Color, text: &str) {
+        self.allegro_data.get_core().draw_text(self.allegro_data.get_std_font(), to_allegro_color(color), pos.0, pos.1, FontAlign::Left, text);
+    }
 }
 
 impl AllegroData {
@@ -81,11 +136,11 @@ impl AllegroData {
 
 
         let allegro_data = AllegroData {
-            core: core,
-            display: display,
-            event_queue: event_queue,
-            primitives_addon: primitives_addon,
-            font_addon: font_addon,
+            core,
+            display,
+            event_queue,
+            primitives_addon,
+            font_addon,
             font_std: font,
             black: Color::from_rgb(0, 0, 0),
             white: Color::from_rgb(0xFF, 0xFF, 0xFF)
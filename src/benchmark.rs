@@ -0,0 +1,128 @@
+use std::time::Instant;
+
+use rand::Rng;
+
+use simulator::Simulator;
+use spatialgrid::SpatialGrid;
+use utility::get_distance;
+use random::create_rng;
+
+//field area and food per bot of the default simulation (2048 x 1536 with 40 bots and 50 food),
+//kept constant so larger populations meet the same density
+const AREA_PER_BOT: f32 = 2048.0 * 1536.0 / 40.0;
+const FOOD_PER_BOT: f32 = 50.0 / 40.0;
+
+pub struct BenchmarkResult {
+    pub bot_count: u32,
+    pub food_count: u32,
+    pub ticks: u64,
+    pub seconds: f64
+}
+
+impl BenchmarkResult {
+
+    pub fn get_ticks_per_second(&self) -> f64 {
+        self.ticks as f64 / self.seconds
+    }
+
+    //time per bot and tick, stays flat as long as the simulation scales linearly
+    pub fn get_micros_per_bot_tick(&self) -> f64 {
+        self.seconds * 1e6 / (self.ticks as f64 * self.bot_count as f64)
+    }
+}
+
+fn get_seconds(start: Instant) -> f64 {
+    let elapsed = start.elapsed();
+    elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9
+}
+
+//runs a simulation of every population size for ticks cycles
+pub fn run_simulation_benchmark(bot_counts: &[u32], ticks: u64, seed: u64) -> Result<Vec<BenchmarkResult>, String> {
+    let mut results = Vec::new();
+
+    for &bot_count in bot_counts {
+        let side = (AREA_PER_BOT * bot_count as f32).sqrt();
+        let field_size = ((side * 4.0 / 3.0) as i32, (side * 3.0 / 4.0) as i32);
+        let food_count = ((FOOD_PER_BOT * bot_count as f32) as u32).max(1);
+
+        let mut sim = Simulator::with_seed(field_size, bot_count, seed)?;
+        sim.set_food_count(food_count);
+
+        let start = Instant::now();
+        sim.fast_forward(ticks as u32);
+
+        results.push(BenchmarkResult {
+            bot_count,
+            food_count,
+            ticks,
+            seconds: get_seconds(start)
+        });
+    }
+
+    Ok(results)
+}
+
+//seconds for query_count proximity queries among object_count objects, with the grid and with a linear scan
+pub fn run_query_benchmark(object_count: usize, query_count: usize, seed: u64) -> (f64, f64) {
+    let mut rng = create_rng(seed);
+    let side = (AREA_PER_BOT * object_count as f32).sqrt();
+    let field_size = (side, side);
+    let radius = 100.0;
+
+    let circles: Vec<((f32, f32), f32)> = (0..object_count)
+        .map(| _ | ((rng.gen_range(0.0, field_size.0), rng.gen_range(0.0, field_size.1)), 10.0))
+        .collect();
+    let centers: Vec<(f32, f32)> = (0..query_count)
+        .map(| _ | (rng.gen_range(0.0, field_size.0), rng.gen_range(0.0, field_size.1)))
+        .collect();
+
+    let start = Instant::now();
+    let mut grid = SpatialGrid::new(field_size, radius);
+    grid.rebuild(circles.iter().cloned());
+    let mut grid_found = 0;
+    for &center in centers.iter() {
+        grid_found += grid.query(center, radius)
+            .into_iter()
+            .filter(| &index | get_distance(center, circles[index].0) < radius + circles[index].1)
+            .count();
+    }
+    let grid_seconds = get_seconds(start);
+
+    let start = Instant::now();
+    let mut linear_found = 0;
+    for &center in centers.iter() {
+        linear_found += circles.iter()
+            .filter(| &&(pos, size) | get_distance(center, pos) < radius + size)
+            .count();
+    }
+    let linear_seconds = get_seconds(start);
+
+    assert_eq!(grid_found, linear_found);
+    (grid_seconds, linear_seconds)
+}
+
+pub fn print_benchmark(bot_counts: &[u32], ticks: u64, seed: u64) -> Result<(), String> {
+    println!("simulation, {} ticks per population:", ticks);
+    println!("{:>8} {:>8} {:>12} {:>16}", "bots", "food", "ticks/s", "us/bot/tick");
+    for result in run_simulation_benchmark(bot_counts, ticks, seed)? {
+        println!("{:>8} {:>8} {:>12.1} {:>16.2}",
+                 result.bot_count,
+                 result.food_count,
+                 result.get_ticks_per_second(),
+                 result.get_micros_per_bot_tick());
+    }
+
+    println!();
+    println!("proximity queries, one per object:");
+    println!("{:>8} {:>12} {:>12} {:>8}", "objects", "grid ms", "linear ms", "speedup");
+    for &count in bot_counts {
+        let (grid_seconds, linear_seconds) = run_query_benchmark(count as usize, count as usize, seed);
+        println!("{:>8} {:>12.2} {:>12.2} {:>7.1}x",
+                 count,
+                 grid_seconds * 1e3,
+                 linear_seconds * 1e3,
+                 linear_seconds / grid_seconds.max(1e-9));
+    }
+
+    Ok(())
+}
@@ -2,16 +2,28 @@ use std::f32;
 use std::f32::consts::PI;
 use std::option::Option;
 
+use rand::Rng;
 use rand::distributions::{ Range, IndependentSample };
-use rand;
-use allegro;
 
 use neuralnet::NeuralNet;
-use allegrodata::{ AllegroData, Drawable };
+use activation::Activation;
+use loss::Loss;
+use optimizer::Momentum;
+use reinforcement::QLearner;
+use color::Color;
 use utility::{ get_distance, line_intersects_line, Vector2D };
 use environment::Environment;
+use teacher::Teacher;
+use sensor::{ Sensor, get_input_size };
 use food::Food;
 
+//with a momentum of 0.9 this gives roughly the step size of the former plain sgd with rate 1.0,
+//but smoothed over the feedback of the last ticks
+const LEARNING_RATE: f64 = 0.1;
+const MOMENTUM: f64 = 0.9;
+//the view radius of a bot in multiples of its size
+pub const VIEW_RADIUS_FACTOR: f32 = 10.0;
+
 pub struct Bot {
     nn: NeuralNet,
     pos: (f32, f32),
@@ -22,64 +34,135 @@ pub struct Bot {
     view_radius: f32,
     fov: f32,
     energy: u32,
-    color: allegro::Color
+    age: u64,
+    eaten_energy: u64,
+    color: Color,
+    learner: Option<QLearner>,
+    sensors: Vec<Sensor>,
+    velocity: f32,
+    id: u64,
+    actions: Vec<f64>,
+    //the sensor values of the last tick, before normalization
+    inputs: Vec<f64>,
+    training_error: Option<f64>
 }
 
-pub enum Direction {
-    Left,
-    Right
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeathCause {
+    LeftField,
+    Starved
 }
 
-impl Drawable for Bot {
-    fn draw(&self, allegro_data: &AllegroData, camera_pos: (f32, f32), scale: (f32, f32)) {
-        //let x = self.pos.0 + self.view_radius * f32::cos(self.rot);
-        //let y = self.pos.1 + self.view_radius * f32::sin(self.rot);
-        //allegro_wrapper.draw_line(self.pos.0, self.pos.1, x, y, allegro_wrapper.get_white(), 1.0);
-
+impl DeathCause {
 
-        allegro_data.get_primitives_addon().draw_pieslice(
-                                        (self.pos.0 - camera_pos.0) * scale.0,
-                                        (self.pos.1 - camera_pos.1) * scale.1,
-                                        self.view_radius * scale.0,
-                                        self.rot - self.fov / 2.0,
-                                        self.fov,
-                                        allegro_data.get_white(),
-                                        1.0);
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            DeathCause::LeftField => "left_field",
+            DeathCause::Starved => "starved"
+        }
+    }
 
-        allegro_data.get_primitives_addon().draw_filled_circle(
-                                        (self.pos.0 - camera_pos.0) * scale.0,
-                                        (self.pos.1 - camera_pos.1) * scale.1,
-                                        self.size * scale.0,
-                                        self.color);
+    pub fn from_name(name: &str) -> Result<DeathCause, String> {
+        match name {
+            "left_field" => Ok(DeathCause::LeftField),
+            "starved" => Ok(DeathCause::Starved),
+            _ => Err(format!("Unknown death cause \"{}\"", name))
+        }
     }
 }
 
+pub enum Direction {
+    Left,
+    Right
+}
+
 impl Bot {
 
-    pub fn new(layers: usize, units: usize, size: f32, speed: f32) -> Bot {
-        let mut nn = NeuralNet::new(layers, units);
+    pub fn new<R: Rng>(topology: &[usize], size: f32, speed: f32, rng: &mut R) -> Bot {
+        let mut nn = NeuralNet::new(topology);
+
+        nn.randomize(-1.0, 1.0, rng);
+
+        Bot::with_net(nn, size, speed, rng)
+    }
 
-        nn.randomize(-1.0, 1.0);
+    pub fn with_net<R: Rng>(mut nn: NeuralNet, size: f32, speed: f32, rng: &mut R) -> Bot {
+        nn.set_optimizer(Box::new(Momentum::new(MOMENTUM)));
 
         let mut bot = Bot {
-            nn: nn,
+            nn,
             pos: (0.0, 0.0),
             rot: 0.0,
-            size: size,
-            speed: speed,
+            size,
+            speed,
             rotation_speed: PI / 15.0,
-            view_radius: 10.0 * size,
+            view_radius: VIEW_RADIUS_FACTOR * size,
             fov: PI / 2.0,
             energy: 1000,
-            color: allegro::Color::from_rgb(0xFF, 0xFF, 0xFF)
+            age: 0,
+            eaten_energy: 0,
+            color: Color::from_rgb(0xFF, 0xFF, 0xFF),
+            learner: None,
+            sensors: Sensor::default_suite(),
+            velocity: 0.0,
+            id: 0,
+            actions: Vec::new(),
+            inputs: Vec::new(),
+            training_error: None
         };
 
-        bot.randomize_color();
+        bot.randomize_color(rng);
 
         bot
     }
 
-    pub fn process(&mut self, environment: Environment) {
+    //with a learner the bot learns from rewards, otherwise from the expected output of the environment
+    pub fn set_learner(&mut self, learner: Option<QLearner>) {
+        if learner.is_some() {
+            //action values are unbounded and the occasional large reward should not dominate
+            self.nn.set_output_activation(Activation::Linear);
+            self.nn.set_loss(Loss::Huber(1.0));
+        }
+        self.learner = learner;
+    }
+
+    pub fn get_learner(&self) -> Option<&QLearner> {
+        self.learner.as_ref()
+    }
+
+    pub fn get_learner_mut(&mut self) -> Option<&mut QLearner> {
+        self.learner.as_mut()
+    }
+
+    //the inputs of the bot's network, their total size must match the input size of the network
+    pub fn set_sensors(&mut self, sensors: Vec<Sensor>) {
+        assert_eq!(get_input_size(&sensors), self.nn.get_input_size());
+        self.sensors = sensors;
+    }
+
+    pub fn get_sensors(&self) -> &[Sensor] {
+        &self.sensors
+    }
+
+    pub fn process<R: Rng>(&mut self, environment: Environment, teacher: &dyn Teacher, rng: &mut R) {
+
+        self.velocity = 0.0;
+        self.training_error = None;
+        self.inputs.clear();
+        self.inputs.extend_from_slice(environment.get_input());
+
+        match self.learner {
+            Some(_) => self.process_reinforced(&environment, rng),
+            None => self.process_supervised(&environment, teacher)
+        }
+
+        if self.energy > 0{
+            self.energy -= 1;
+        }
+        self.age += 1;
+    }
+
+    fn process_supervised(&mut self, environment: &Environment, teacher: &dyn Teacher) {
 
         let actions = self.nn.feed_forward(environment.get_input());
 
@@ -90,47 +173,85 @@ impl Bot {
 
         self.move_forward(actions[2] as f32);
 
-        let feedback = environment.get_expected_output(&actions);
+        if let Some(feedback) = teacher.get_expected_output(environment, &actions) {
+            self.give_feedback(&feedback);
+        }
+        self.actions = actions;
+    }
 
+    //every output is the value of one action: turn left, turn right, move forward
+    fn process_reinforced<R: Rng>(&mut self, environment: &Environment, rng: &mut R) {
+        let (action, error) = match self.learner {
+            Some(ref mut learner) => learner.select_action(&mut self.nn, environment.get_input(), rng),
+            None => return
+        };
+        self.training_error = Some(error);
+
+        match action {
+            0 => {
+                self.rotate(1.0);
+                self.move_forward(0.5);
+            },
+            1 => {
+                self.rotate(-1.0);
+                self.move_forward(0.5);
+            },
+            _ => self.move_forward(1.0)
+        }
 
-        self.give_feedback(&feedback);
+        self.actions = vec![0.0; self.nn.get_output_size()];
+        self.actions[action] = 1.0;
+    }
 
-        if self.energy > 0{
-            self.energy -= 1;
+    //only has an effect on bots with a learner
+    pub fn reward(&mut self, reward: f64) {
+        if let Some(ref mut learner) = self.learner {
+            learner.add_reward(reward);
+        }
+    }
+
+    //gives the learner the final reward of the bot's life
+    pub fn die<R: Rng>(&mut self, reward: f64, rng: &mut R) {
+        if let Some(ref mut learner) = self.learner {
+            learner.finish(&mut self.nn, reward, rng);
         }
     }
 
-    pub fn give_feedback(&mut self, feedback: &Vec<f64>) {
-        self.nn.backpropagate(feedback, 1.0);
+    pub fn give_feedback(&mut self, feedback: &[f64]) {
+        self.training_error = Some(self.nn.backpropagate(feedback, LEARNING_RATE));
+    }
+
+    //the error of the training in the last tick, None if the bot did not train
+    pub fn get_training_error(&self) -> Option<f64> {
+        self.training_error
     }
 
-    pub fn randomize_net(&mut self, min: f64, max: f64) {
-        self.nn.randomize(min, max);
+    pub fn randomize_net<R: Rng>(&mut self, min: f64, max: f64, rng: &mut R) {
+        self.nn.randomize(min, max, rng);
     }
 
-    pub fn randomize_pos_rot(&mut self, field_size: (f32, f32)) {
+    pub fn randomize_pos_rot<R: Rng>(&mut self, field_size: (f32, f32), rng: &mut R) {
         let range_x = Range::new(0.0, field_size.0);
         let range_y = Range::new(0.0, field_size.1);
         let range_rot = Range::new(0.0, 2.0 * PI);
-        let mut rng = rand::thread_rng();
 
-        self.pos = (range_x.ind_sample(&mut rng), range_y.ind_sample(&mut rng));
-        self.rot = range_rot.ind_sample(&mut rng);
+        self.pos = (range_x.ind_sample(rng), range_y.ind_sample(rng));
+        self.rot = range_rot.ind_sample(rng);
     }
 
-    pub fn randomize_color(&mut self) {
+    pub fn randomize_color<R: Rng>(&mut self, rng: &mut R) {
         let range = Range::new(0, 0xFF);
-        let mut rng = rand::thread_rng();
 
-        let r = range.ind_sample(&mut rng);
-        let g = range.ind_sample(&mut rng);
-        let b = range.ind_sample(&mut rng);
-        self.color = allegro::Color::from_rgb(r, g, b);
+        let r = range.ind_sample(rng);
+        let g = range.ind_sample(rng);
+        let b = range.ind_sample(rng);
+        self.color = Color::from_rgb(r, g, b);
     }
 
     fn move_forward(&mut self, factor: f32) {
         self.pos.0 += self.speed * factor * f32::cos(self.rot);
         self.pos.1 += self.speed * factor * f32::sin(self.rot);
+        self.velocity += self.speed * factor;
     }
 
     pub fn rotate(&mut self, strength: f32) {
@@ -144,6 +265,33 @@ impl Bot {
         self.pos.1 < field_size.1
     }
 
+    //None while the bot is alive
+    pub fn get_death_cause(&self, field_size: (f32, f32)) -> Option<DeathCause> {
+        match (self.in_boundary(field_size), self.energy) {
+            (false, _) => Some(DeathCause::LeftField),
+            (true, 0) => Some(DeathCause::Starved),
+            _ => None
+        }
+    }
+
+    //unique within a simulation, assigned by the simulator
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn set_id(&mut self, id: u64) {
+        self.id = id;
+    }
+
+    pub fn get_inputs(&self) -> &[f64] {
+        &self.inputs
+    }
+
+    //the network output of the last tick, for learning bots the chosen action as one-hot vector
+    pub fn get_actions(&self) -> &[f64] {
+        &self.actions
+    }
+
     pub fn get_pos(&self) -> (f32, f32) {
         self.pos
     }
@@ -156,6 +304,26 @@ impl Bot {
         self.rot
     }
 
+    pub fn set_rotation(&mut self, rot: f32) {
+        self.rot = rot;
+    }
+
+    pub fn get_size(&self) -> f32 {
+        self.size
+    }
+
+    pub fn get_color(&self) -> Color {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    pub fn get_speed(&self) -> f32 {
+        self.speed
+    }
+
     pub fn get_view_radius(&self) -> f32 {
         self.view_radius
     }
@@ -164,10 +332,52 @@ impl Bot {
         self.fov
     }
 
+    //distance moved in the last tick
+    pub fn get_velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    pub fn set_velocity(&mut self, velocity: f32) {
+        self.velocity = velocity;
+    }
+
     pub fn get_energy(&self) -> u32 {
         self.energy
     }
 
+    pub fn set_energy(&mut self, energy: u32) {
+        self.energy = energy;
+    }
+
+    pub fn get_age(&self) -> u64 {
+        self.age
+    }
+
+    pub fn set_age(&mut self, age: u64) {
+        self.age = age;
+    }
+
+    pub fn get_eaten_energy(&self) -> u64 {
+        self.eaten_energy
+    }
+
+    pub fn set_eaten_energy(&mut self, eaten_energy: u64) {
+        self.eaten_energy = eaten_energy;
+    }
+
+    //the energy a bot gathered over its life, used to rank bots for evolution
+    pub fn get_fitness(&self) -> f64 {
+        self.eaten_energy as f64
+    }
+
+    pub fn get_net(&self) -> &NeuralNet {
+        &self.nn
+    }
+
+    pub fn get_net_mut(&mut self) -> &mut NeuralNet {
+        &mut self.nn
+    }
+
     pub fn mod_energy(&mut self, value: u32) {
         self.energy += value;
     }
@@ -184,6 +394,7 @@ impl Bot {
 
     pub fn eat(&mut self, food: Food) {
         self.energy += food.get_energy();
+        self.eaten_energy += food.get_energy() as u64;
 
         //self.view_radius *= 1.1;
 
@@ -236,10 +447,7 @@ impl Bot {
                 let right_intersection = line_intersects_line(right_ray, line);
 
 
-                if left_intersection != None && right_intersection != None {
-
-                    let left_intersection = left_intersection.unwrap();
-                    let right_intersection = right_intersection.unwrap();
+                if let (Some(left_intersection), Some(right_intersection)) = (left_intersection, right_intersection) {
 
                     //direction can be +1.0 or -1.0, depending on if the intersection is in view or 180° behind it
                     let left_direction = self.get_rotated_view_vector(-fov_half).normalize().dot(left_intersection.sub(self.pos).normalize());
@@ -0,0 +1,179 @@
+//Parsing of command line options: "--name value", "--name=value" and flags like "--verbose".
+//Other arguments are positional, "--" ends the options. "--help" and "-h" are always accepted.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[derive(Clone, Copy)]
+pub struct OptionSpec {
+    pub name: &'static str,
+    //the placeholder of the value shown in the help, None for flags
+    pub value: Option<&'static str>,
+    pub help: &'static str
+}
+
+pub const HELP_OPTION: OptionSpec = OptionSpec { name: "help", value: None, help: "print this help" };
+
+pub struct Arguments {
+    //flags map to an empty string
+    options: HashMap<String, String>,
+    positional: Vec<String>
+}
+
+impl Arguments {
+
+    pub fn parse(args: &[String], specs: &[OptionSpec]) -> Result<Arguments, String> {
+        let mut arguments = Arguments {
+            options: HashMap::new(),
+            positional: Vec::new()
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                arguments.positional.extend(args.cloned());
+                break;
+            }
+            if arg == "-h" {
+                arguments.options.insert(String::from(HELP_OPTION.name), String::new());
+                continue;
+            }
+            if !arg.starts_with("--") {
+                arguments.positional.push(arg.clone());
+                continue;
+            }
+
+            let (name, inline_value) = match arg[2..].find('=') {
+                Some(index) => (&arg[2..2 + index], Some(arg[3 + index..].to_string())),
+                None => (&arg[2..], None)
+            };
+            let spec = match specs.iter().chain(Some(&HELP_OPTION)).find(| spec | spec.name == name) {
+                Some(spec) => spec,
+                None => return Err(format!("Unknown option --{}", name))
+            };
+
+            let value = match (spec.value, inline_value) {
+                (Some(_), Some(value)) => value,
+                (Some(placeholder), None) => match args.next() {
+                    Some(value) => value.clone(),
+                    None => return Err(format!("Missing {} after --{}", placeholder, name))
+                },
+                (None, Some(_)) => return Err(format!("The flag --{} takes no value", name)),
+                (None, None) => String::new()
+            };
+            arguments.options.insert(String::from(name), value);
+        }
+        Ok(arguments)
+    }
+
+    pub fn has_flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(| value | value.as_str())
+    }
+
+    //the value of the option converted by parse, None if the option is not given
+    pub fn get_with<T, F>(&self, name: &str, parse: F) -> Result<Option<T>, String>
+        where F: Fn(&str) -> Result<T, String>
+    {
+        match self.get_str(name) {
+            Some(value) => parse(value).map(Some).map_err(| e | format!("Invalid value for --{}: {}", name, e)),
+            None => Ok(None)
+        }
+    }
+
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.get_with(name, | value | value.parse::<T>().map_err(| _ | format!("\"{}\"", value)))
+    }
+
+    pub fn get_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        self.get(name).map(| value | value.unwrap_or(default))
+    }
+
+    pub fn get_positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    //the positional argument at index, which is described by name in the error if it's missing
+    pub fn get_required(&self, index: usize, name: &str) -> Result<&str, String> {
+        match self.positional.get(index) {
+            Some(value) => Ok(value),
+            None => Err(format!("Missing {}", name))
+        }
+    }
+}
+
+//two numbers joined by "x", e.g. "1024x768"
+pub fn parse_size<T: FromStr>(text: &str) -> Result<(T, T), String> {
+    let mut parts = text.splitn(2, 'x');
+    match (parts.next().map(str::parse::<T>), parts.next().map(str::parse::<T>)) {
+        (Some(Ok(width)), Some(Ok(height))) => Ok((width, height)),
+        _ => Err(format!("Expected a size like 1024x768, got \"{}\"", text))
+    }
+}
+
+//two numbers joined by ",", e.g. "512.5,300"
+pub fn parse_point<T: FromStr>(text: &str) -> Result<(T, T), String> {
+    let mut parts = text.splitn(2, ',');
+    match (parts.next().map(| part | part.trim().parse::<T>()), parts.next().map(| part | part.trim().parse::<T>())) {
+        (Some(Ok(x)), Some(Ok(y))) => Ok((x, y)),
+        _ => Err(format!("Expected a position like 512,300, got \"{}\"", text))
+    }
+}
+
+//comma separated values, e.g. "rays:8,energy"
+pub fn parse_list<T, F>(text: &str, parse: F) -> Result<Vec<T>, String>
+    where F: Fn(&str) -> Result<T, String>
+{
+    text.split(',').map(| item | parse(item.trim())).collect()
+}
+
+pub fn format_help(usage: &str, description: &str, specs: &[OptionSpec]) -> String {
+    let columns: Vec<(String, &str)> = specs.iter()
+        .chain(Some(&HELP_OPTION))
+        .map(| spec | match spec.value {
+            Some(placeholder) => (format!("--{} {}", spec.name, placeholder), spec.help),
+            None => (format!("--{}", spec.name), spec.help)
+        })
+        .collect();
+    let width = columns.iter().map(| (column, _) | column.len()).max().unwrap_or(0);
+
+    let mut help = format!("usage: {}\n\n{}\n\noptions:\n", usage, description);
+    for (column, text) in columns {
+        help += &format!("  {:width$}  {}\n", column, text, width = width);
+    }
+    help
+}
+
+#[test]
+fn test_parse_arguments() {
+    let specs = [OptionSpec { name: "bots", value: Some("COUNT"), help: "" },
+                 OptionSpec { name: "field", value: Some("WxH"), help: "" },
+                 OptionSpec { name: "verbose", value: None, help: "" }];
+    let args: Vec<String> = ["data.txt", "--bots", "20", "--field=800x600", "--verbose", "--", "--bots"]
+        .iter().map(| arg | arg.to_string()).collect();
+
+    let arguments = Arguments::parse(&args, &specs).unwrap();
+    assert!(arguments.get::<u32>("bots") == Ok(Some(20)));
+    assert!(arguments.get_with("field", parse_size::<i32>) == Ok(Some((800, 600))));
+    assert!(arguments.has_flag("verbose") && !arguments.has_flag("help"));
+    assert!(arguments.get_or("seed", 3u64) == Ok(3));
+    assert!(arguments.get_positional() == ["data.txt", "--bots"]);
+    assert!(arguments.get_required(2, "dataset").is_err());
+
+    let parse = | args: &[&str] | Arguments::parse(&args.iter().map(| arg | arg.to_string()).collect::<Vec<String>>(), &specs);
+    assert!(parse(&["--seed", "3"]).is_err());
+    assert!(parse(&["--bots"]).is_err());
+    assert!(parse(&["--verbose=1"]).is_err());
+    assert!(parse(&["--bots", "many"]).unwrap().get::<u32>("bots").is_err());
+    assert!(parse(&["-h"]).unwrap().has_flag("help"));
+
+    assert!(parse_list("1,2, 3", | item | item.parse::<usize>().map_err(| e | e.to_string())) == Ok(vec![1, 2, 3]));
+    assert!(parse_size::<u32>("800").is_err());
+    assert!(parse_point::<f32>("512.5, 300") == Ok((512.5, 300.0)));
+    assert!(parse_point::<f32>("512.5").is_err());
+    let help = format_help("neural_net bench", "Benchmarks.", &specs);
+    assert!(help.contains("\n  --field WxH   \n") && help.contains("\n  --help        print this help\n"));
+}
@@ -0,0 +1,35 @@
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    r: u8,
+    g: u8,
+    b: u8
+}
+
+impl Color {
+
+    pub fn from_rgb(r: u8, g: u8, b: u8) -> Color {
+        Color {
+            r,
+            g,
+            b
+        }
+    }
+
+    pub fn get_rgb(&self) -> (u8, u8, u8) {
+        (self.r, self.g, self.b)
+    }
+
+    //blends linearly towards other, t is clamped to [0, 1]
+    pub fn mix(&self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let channel = | a: u8, b: u8 | (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::from_rgb(channel(self.r, other.r), channel(self.g, other.g), channel(self.b, other.b))
+    }
+
+    //as used by svg and html, e.g. #ff8000
+    pub fn get_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+}
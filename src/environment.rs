@@ -1,14 +1,26 @@
-use std::f64::consts::PI;
+use sensor::{ Sensor, get_input_size };
 
 pub struct Environment {
     input: Vec<f64>,
+    sensors: Vec<Sensor>,
+    //index of the first input of every sensor
+    offsets: Vec<usize>,
 }
 
 impl Environment {
 
-    pub fn new(size: usize) -> Environment {
+    pub fn new(sensors: &[Sensor]) -> Environment {
+        let mut offsets = Vec::with_capacity(sensors.len());
+        let mut offset = 0;
+        for sensor in sensors {
+            offsets.push(offset);
+            offset += sensor.get_size();
+        }
+
         Environment {
-            input: vec![0.0; size],
+            input: vec![0.0; get_input_size(sensors)],
+            sensors: sensors.to_vec(),
+            offsets,
         }
     }
 
@@ -20,33 +32,25 @@ impl Environment {
         &self.input
     }
 
-    #[allow(unused_variables)]
-    pub fn get_expected_output(&self, output: &Vec<f64>) -> Vec<f64> {
-        let mut target_output = vec![0.0; self.input.len()];
-
-        if self.input[0] > 0.0 {
-            match self.input[1] {
-                e if e > 0.0 && e < PI => target_output[1] = 1.0,
-                e if e < 0.0 && e > -PI => target_output[0] = 1.0,
-                _ => {}
-            }
-            target_output[2] = 0.0;
-        }
-        else if self.input[2] > 0.0 {
-            match self.input[3] {
-                e if e > 0.0 => target_output[0] = e,
-                e if e < 0.0 => target_output[1] = -e,
-                _ => {}
-            }
-            target_output[2] = 0.5;
-        }
-        else{
-            target_output[0] = 0.0; //output[0];
-            target_output[1] = 0.0; //output[1];
-            target_output[2] = 1.0;
-        }
+    pub fn get_sensors(&self) -> &[Sensor] {
+        &self.sensors
+    }
+
+    pub fn set_sensor_input(&mut self, sensor_index: usize, values: &[f64]) {
+        assert_eq!(values.len(), self.sensors[sensor_index].get_size());
+
+        let offset = self.offsets[sensor_index];
+        self.input[offset..offset + values.len()].copy_from_slice(values);
+    }
 
-        target_output
+    //the inputs of the first sensor equal to sensor, None if the environment has no such sensor
+    pub fn get_sensor_input(&self, sensor: Sensor) -> Option<&[f64]> {
+        self.sensors.iter()
+            .position(| &e | e == sensor)
+            .map(| index | {
+                let offset = self.offsets[index];
+                &self.input[offset..offset + sensor.get_size()]
+            })
     }
 
 }
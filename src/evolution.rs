@@ -0,0 +1,333 @@
+use std::cmp::Ordering;
+
+use rand::Rng;
+use rand::distributions::{ Normal, IndependentSample };
+
+use neuralnet::NeuralNet;
+use bot::Bot;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Selection {
+    //the fittest of n randomly picked candidates
+    Tournament(usize),
+    //picks candidates with a probability proportional to their fitness
+    Roulette,
+    //picks uniformly from the n fittest candidates
+    Elitism(usize)
+}
+
+impl Selection {
+
+    pub fn get_name(&self) -> String {
+        match *self {
+            Selection::Tournament(size) => format!("tournament:{}", size),
+            Selection::Roulette => String::from("roulette"),
+            Selection::Elitism(count) => format!("elitism:{}", count)
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Selection, String> {
+        let parse_count = | prefix: &str | match name[prefix.len()..].parse::<usize>() {
+            Ok(count) if count > 0 => Ok(count),
+            _ => Err(format!("Invalid candidate count in \"{}\"", name))
+        };
+
+        match name {
+            "roulette" => Ok(Selection::Roulette),
+            _ if name.starts_with("tournament:") => parse_count("tournament:").map(Selection::Tournament),
+            _ if name.starts_with("elitism:") => parse_count("elitism:").map(Selection::Elitism),
+            _ => Err(format!("Unknown selection \"{}\"", name))
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Genome {
+    parameters: Vec<f64>,
+    fitness: f64
+}
+
+impl Genome {
+
+    pub fn new(parameters: Vec<f64>, fitness: f64) -> Genome {
+        Genome {
+            parameters,
+            fitness
+        }
+    }
+
+    pub fn from_bot(bot: &Bot) -> Genome {
+        Genome::new(bot.get_net().get_parameters(), bot.get_fitness())
+    }
+
+    pub fn get_parameters(&self) -> &[f64] {
+        &self.parameters
+    }
+
+    pub fn get_fitness(&self) -> f64 {
+        self.fitness
+    }
+}
+
+fn compare_fitness(a: &Genome, b: &Genome) -> Ordering {
+    b.fitness.partial_cmp(&a.fitness).unwrap_or(Ordering::Equal)
+}
+
+//Breeds the networks of new bots from the living bots and an archive of the fittest dead bots.
+pub struct Evolution {
+    selection: Selection,
+    mutation_rate: f64,
+    mutation_strength: f64,
+    archive_size: usize,
+    archive: Vec<Genome>
+}
+
+impl Evolution {
+
+    pub fn new(selection: Selection, mutation_rate: f64) -> Evolution {
+        assert!((0.0..=1.0).contains(&mutation_rate));
+
+        Evolution {
+            selection,
+            mutation_rate,
+            mutation_strength: 0.3,
+            archive_size: 20,
+            archive: Vec::new()
+        }
+    }
+
+    //standard deviation of the noise added to a mutated parameter
+    pub fn mutation_strength(mut self, mutation_strength: f64) -> Self {
+        assert!(mutation_strength > 0.0);
+        self.mutation_strength = mutation_strength;
+        self
+    }
+
+    //count of the fittest dead bots, which stay available as parents
+    pub fn archive_size(mut self, archive_size: usize) -> Self {
+        self.archive_size = archive_size;
+        self.archive.truncate(archive_size);
+        self
+    }
+
+    pub fn get_selection(&self) -> Selection {
+        self.selection
+    }
+
+    pub fn get_mutation_rate(&self) -> f64 {
+        self.mutation_rate
+    }
+
+    pub fn get_archive(&self) -> &[Genome] {
+        &self.archive
+    }
+
+    //replaces the archive, e.g. when a snapshot is restored
+    pub fn set_archive(&mut self, mut archive: Vec<Genome>) {
+        archive.sort_by(compare_fitness);
+        archive.truncate(self.archive_size);
+        self.archive = archive;
+    }
+
+    //keeps the genome of a dead bot, if it is among the fittest
+    pub fn record(&mut self, bot: &Bot) {
+        if self.archive_size == 0 {
+            return;
+        }
+        if self.archive.len() >= self.archive_size &&
+           self.archive.last().is_some_and(| worst | worst.fitness >= bot.get_fitness()) {
+            return;
+        }
+
+        self.archive.push(Genome::from_bot(bot));
+        self.archive.sort_by(compare_fitness);
+        self.archive.truncate(self.archive_size);
+    }
+
+    //creates a child of template's structure from two selected parents,
+    //None if there are no candidates with a matching structure
+    pub fn breed<R: Rng>(&self, template: &NeuralNet, living: &[Bot], rng: &mut R) -> Option<NeuralNet> {
+        let parameter_count = template.get_parameter_count();
+
+        let mut candidates: Vec<Genome> = living.iter()
+            .map(Genome::from_bot)
+            .chain(self.archive.iter().cloned())
+            .filter(| genome | genome.parameters.len() == parameter_count)
+            .collect();
+
+        if candidates.is_empty() {
+            return None;
+        }
+        candidates.sort_by(compare_fitness);
+
+        let parameters = {
+            let parent_a = self.select(&candidates, rng);
+            let parent_b = self.select(&candidates, rng);
+            self.mutate(self.crossover(parent_a, parent_b, rng), rng)
+        };
+
+        let mut child = template.clone_structure();
+        child.set_parameters(&parameters);
+        Some(child)
+    }
+
+    //candidates must be sorted by descending fitness
+    fn select<'a, R: Rng>(&self, candidates: &'a [Genome], rng: &mut R) -> &'a Genome {
+        match self.selection {
+            Selection::Tournament(size) => {
+                let mut best = &candidates[rng.gen_range(0, candidates.len())];
+                for _ in 1..size.max(1) {
+                    let candidate = &candidates[rng.gen_range(0, candidates.len())];
+                    if candidate.fitness > best.fitness {
+                        best = candidate;
+                    }
+                }
+                best
+            },
+            Selection::Roulette => {
+                let total = candidates.iter().fold(0.0, | sum, genome | sum + genome.fitness.max(0.0));
+                if total <= 0.0 {
+                    return &candidates[rng.gen_range(0, candidates.len())];
+                }
+
+                let mut target = rng.gen::<f64>() * total;
+                for genome in candidates {
+                    target -= genome.fitness.max(0.0);
+                    if target <= 0.0 {
+                        return genome;
+                    }
+                }
+                &candidates[candidates.len() - 1]
+            },
+            Selection::Elitism(count) => {
+                let count = count.max(1).min(candidates.len());
+                &candidates[rng.gen_range(0, count)]
+            }
+        }
+    }
+
+    //uniform crossover, every parameter comes from either parent
+    fn crossover<R: Rng>(&self, parent_a: &Genome, parent_b: &Genome, rng: &mut R) -> Vec<f64> {
+        parent_a.parameters.iter()
+            .zip(parent_b.parameters.iter())
+            .map(| (&a, &b) | match rng.gen::<bool>() {
+                true => a,
+                false => b
+            })
+            .collect()
+    }
+
+    fn mutate<R: Rng>(&self, mut parameters: Vec<f64>, rng: &mut R) -> Vec<f64> {
+        let noise = Normal::new(0.0, self.mutation_strength);
+        for param in parameters.iter_mut() {
+            if rng.gen::<f64>() < self.mutation_rate {
+                *param += noise.ind_sample(rng);
+            }
+        }
+        parameters
+    }
+}
+
+#[test]
+fn test_breed_without_mutation() {
+    use random::create_rng;
+
+    let mut rng = create_rng(3);
+    let bot = Bot::new(&[4, 5, 3], 10.0, 5.0, &mut rng);
+    let template = NeuralNet::new(&[4, 5, 3]);
+
+    let evolution = Evolution::new(Selection::Tournament(3), 0.0);
+    let child = evolution.breed(&template, &[], &mut rng);
+    assert!(child.is_none());
+
+    let mut evolution = Evolution::new(Selection::Roulette, 0.0);
+    evolution.record(&bot);
+    let child = evolution.breed(&template, &[], &mut rng).unwrap();
+    assert!(child.get_parameters() == bot.get_net().get_parameters());
+
+    //genomes of another structure are never used as parents
+    let other_template = NeuralNet::new(&[2, 3, 3]);
+    assert!(evolution.breed(&other_template, &[], &mut rng).is_none());
+}
+
+//genomes whose parameters all equal their fitness, sorted by descending fitness like the candidates of breed
+#[cfg(test)]
+fn get_test_genomes(count: usize, size: usize) -> Vec<Genome> {
+    (0..count).rev().map(| fitness | Genome::new(vec![fitness as f64; size], fitness as f64)).collect()
+}
+
+#[test]
+fn test_selection() {
+    use random::create_rng;
+
+    let mut rng = create_rng(5);
+    let candidates = get_test_genomes(5, 1);
+
+    //the elites are the only parents and carry over unchanged without mutation
+    let elitism = Evolution::new(Selection::Elitism(1), 0.0);
+    for _ in 0..20 {
+        let parent_a = elitism.select(&candidates, &mut rng);
+        let parent_b = elitism.select(&candidates, &mut rng);
+        assert!(elitism.mutate(elitism.crossover(parent_a, parent_b, &mut rng), &mut rng) == vec![4.0]);
+    }
+    let elitism = Evolution::new(Selection::Elitism(2), 0.0);
+    assert!((0..100).all(| _ | elitism.select(&candidates, &mut rng).fitness >= 3.0));
+
+    //the fittest of 3 out of 5 is the best one with a probability of 1 - 0.8^3 = 0.488,
+    //a uniform pick would give 0.2 and a mean fitness of 2
+    let tournament = Evolution::new(Selection::Tournament(3), 0.0);
+    let picks: Vec<f64> = (0..2000).map(| _ | tournament.select(&candidates, &mut rng).fitness).collect();
+    let best = picks.iter().filter(| &&fitness | fitness == 4.0).count();
+    assert!(best > 900 && best < 1050);
+    assert!(picks.iter().filter(| &&fitness | fitness == 0.0).count() < best / 10);
+    assert!(picks.iter().sum::<f64>() / 2000.0 > 3.0);
+}
+
+#[test]
+fn test_mutation() {
+    use random::create_rng;
+
+    let mut rng = create_rng(6);
+    let parameters = vec![0.0; 10000];
+    assert!(Evolution::new(Selection::Roulette, 0.0).mutate(parameters.clone(), &mut rng) == parameters);
+    assert!(Evolution::new(Selection::Roulette, 1.0).mutate(parameters.clone(), &mut rng).iter().all(| &param | param != 0.0));
+
+    //about a tenth of the parameters changes, by noise with a standard deviation of the strength
+    let evolution = Evolution::new(Selection::Roulette, 0.1).mutation_strength(0.5);
+    let changes: Vec<f64> = evolution.mutate(parameters, &mut rng).into_iter().filter(| &param | param != 0.0).collect();
+    assert!(changes.len() > 900 && changes.len() < 1100);
+    let deviation = (changes.iter().map(| change | change * change).sum::<f64>() / changes.len() as f64).sqrt();
+    assert!((deviation - 0.5).abs() < 0.05);
+}
+
+#[test]
+fn test_archive() {
+    use random::create_rng;
+
+    let mut rng = create_rng(7);
+    let mut evolution = Evolution::new(Selection::Roulette, 0.0).archive_size(3);
+    let mut genomes = get_test_genomes(5, 4 * 5 + 5 + 5 * 3 + 3);
+    genomes.reverse();
+    evolution.set_archive(genomes);
+    let fitness = | evolution: &Evolution | evolution.get_archive().iter().map(Genome::get_fitness).collect::<Vec<_>>();
+    assert!(fitness(&evolution) == [4.0, 3.0, 2.0]);
+
+    //a dead bot only enters a full archive if it is fitter than the worst genome
+    let mut bot = Bot::new(&[4, 5, 3], 10.0, 5.0, &mut rng);
+    bot.set_eaten_energy(1);
+    evolution.record(&bot);
+    assert!(fitness(&evolution) == [4.0, 3.0, 2.0]);
+    bot.set_eaten_energy(3);
+    evolution.record(&bot);
+    assert!(fitness(&evolution) == [4.0, 3.0, 3.0]);
+    //the sort is stable, so of equally fit genomes the older one ranks first
+    assert!(evolution.get_archive()[2].get_parameters() == &bot.get_net().get_parameters()[..]);
+
+    //archived genomes are parents even without living bots, the elite carries over unchanged
+    let template = NeuralNet::new(&[4, 5, 3]);
+    let mut elitism = Evolution::new(Selection::Elitism(1), 0.0);
+    elitism.set_archive(evolution.get_archive().to_vec());
+    let child = elitism.breed(&template, &[], &mut rng).unwrap();
+    assert!(child.get_parameters() == evolution.get_archive()[0].get_parameters());
+    assert!(Evolution::new(Selection::Roulette, 0.0).archive_size(0).breed(&template, &[], &mut rng).is_none());
+}
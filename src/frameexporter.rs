@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::PathBuf;
+
+use simulator::Simulator;
+use raster::RasterRenderer;
+use renderer::render_simulation;
+use image::{ ImageFormat, save_image };
+use serialization::io_error;
+
+//Renders every interval-th tick of a simulation into a numbered image file,
+//frame_000000.png, frame_000001.png, ... so the frames can be joined into a video.
+pub struct FrameExporter {
+    directory: PathBuf,
+    size: (usize, usize),
+    interval: u64,
+    format: ImageFormat,
+    //the field position in the middle of the frames, None for the middle of the field
+    camera_center: Option<(f32, f32)>,
+    //1.0 fits the whole field into the frames
+    zoom: f32,
+    frame_count: u64
+}
+
+impl FrameExporter {
+
+    pub fn new(directory: PathBuf, size: (usize, usize)) -> FrameExporter {
+        assert!(size.0 > 0 && size.1 > 0);
+
+        FrameExporter {
+            directory,
+            size,
+            interval: 1,
+            format: ImageFormat::Png,
+            camera_center: None,
+            zoom: 1.0,
+            frame_count: 0
+        }
+    }
+
+    pub fn interval(mut self, interval: u64) -> Self {
+        assert!(interval > 0);
+        self.interval = interval;
+        self
+    }
+
+    pub fn format(mut self, format: ImageFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn camera(mut self, center: (f32, f32), zoom: f32) -> Self {
+        assert!(zoom > 0.0);
+        self.camera_center = Some(center);
+        self.zoom = zoom;
+        self
+    }
+
+    pub fn zoom(mut self, zoom: f32) -> Self {
+        assert!(zoom > 0.0);
+        self.zoom = zoom;
+        self
+    }
+
+    pub fn get_frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn get_frame_path(&self, frame: u64) -> PathBuf {
+        self.directory.join(format!("frame_{:06}.{}", frame, self.format.get_extension()))
+    }
+
+    //the camera position and scale for a simulation
+    pub fn get_view(&self, field_size: (f32, f32)) -> ((f32, f32), (f32, f32)) {
+        let fit = (self.size.0 as f32 / field_size.0).min(self.size.1 as f32 / field_size.1);
+        let scale = fit * self.zoom;
+        let center = self.camera_center.unwrap_or((field_size.0 / 2.0, field_size.1 / 2.0));
+        let camera_pos = (center.0 - self.size.0 as f32 / 2.0 / scale, center.1 - self.size.1 as f32 / 2.0 / scale);
+        (camera_pos, (scale, scale))
+    }
+
+    pub fn render(&self, simulator: &Simulator) -> RasterRenderer {
+        let mut raster = RasterRenderer::new(self.size.0, self.size.1);
+        let (camera_pos, scale) = self.get_view(simulator.get_field_size());
+        render_simulation(&mut raster, simulator, camera_pos, scale);
+        raster
+    }
+
+    //saves a frame if the tick of the simulator is due, returns the path of the saved frame
+    pub fn export(&mut self, simulator: &Simulator) -> Result<Option<PathBuf>, String> {
+        if simulator.get_ticks() % self.interval != 0 {
+            return Ok(None);
+        }
+
+        if self.frame_count == 0 {
+            fs::create_dir_all(&self.directory).map_err(io_error)?;
+        }
+        let path = self.get_frame_path(self.frame_count);
+        save_image(&self.render(simulator), self.format, &path)?;
+        self.frame_count += 1;
+        Ok(Some(path))
+    }
+}
+
+#[test]
+fn test_frame_export() {
+    use std::env;
+    use std::process;
+
+    let directory = env::temp_dir().join(format!("neural_net_frames_{}", process::id()));
+    let mut sim = Simulator::with_seed((800, 600), 10, 3).unwrap();
+    let mut exporter = FrameExporter::new(directory.clone(), (80, 60))
+        .interval(10)
+        .format(ImageFormat::Ppm);
+
+    for _ in 0..30 {
+        sim.cycle();
+        exporter.export(&sim).unwrap();
+    }
+    assert!(exporter.get_frame_count() == 3);
+    let frame = fs::read(exporter.get_frame_path(2)).unwrap();
+    assert!(frame.starts_with(b"P6\n80 60\n255\n") && frame.len() == 13 + 80 * 60 * 3);
+    assert!(!exporter.get_frame_path(3).exists());
+
+    //zooming in on the middle halves the visible part of the field
+    let zoomed = FrameExporter::new(directory.clone(), (80, 60)).zoom(2.0);
+    assert!(zoomed.get_view((800.0, 600.0)) == ((200.0, 150.0), (0.2, 0.2)));
+
+    fs::remove_dir_all(directory).unwrap();
+}
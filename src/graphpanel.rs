@@ -0,0 +1,111 @@
+use allegro::Color;
+use allegro_font::{ FontDrawing, FontAlign };
+
+use allegrodata::AllegroData;
+use statistics::{ Statistics, Metric };
+
+const CHART_SPACING: f32 = 6.0;
+const LABEL_HEIGHT: f32 = 12.0;
+const PADDING: f32 = 4.0;
+
+//An overlay with one rolling chart per metric, stacked from top to bottom.
+//Every chart is scaled from zero to the largest value it currently shows.
+pub struct GraphPanel {
+    pos: (f32, f32),
+    size: (f32, f32),
+    //the number of ticks a chart spans
+    length: usize,
+    metrics: Vec<(Metric, Color)>,
+    visible: bool
+}
+
+impl GraphPanel {
+
+    pub fn new(pos: (f32, f32), size: (f32, f32)) -> GraphPanel {
+        GraphPanel {
+            pos,
+            size,
+            length: 2000,
+            metrics: vec![(Metric::Population, Color::from_rgb(0x40, 0xC0, 0x40)),
+                          (Metric::MeanEnergy, Color::from_rgb(0xE0, 0xC0, 0x20)),
+                          (Metric::FoodEatenPer100, Color::from_rgb(0xC0, 0x50, 0x20)),
+                          (Metric::MeanAge, Color::from_rgb(0x40, 0x90, 0xE0))],
+            visible: false
+        }
+    }
+
+    pub fn length(mut self, length: usize) -> Self {
+        assert!(length > 1);
+        self.length = length;
+        self
+    }
+
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    pub fn metrics(mut self, metrics: Vec<(Metric, Color)>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn draw(&self, allegro_data: &AllegroData, statistics: &Statistics) {
+        if !self.visible || self.metrics.is_empty() {
+            return;
+        }
+
+        let primitives = allegro_data.get_primitives_addon();
+        primitives.draw_filled_rectangle(self.pos.0, self.pos.1, self.pos.0 + self.size.0, self.pos.1 + self.size.1, Color::from_rgba(0, 0, 0, 200));
+
+        let chart_height = (self.size.1 - CHART_SPACING * (self.metrics.len() + 1) as f32) / self.metrics.len() as f32;
+        for (index, &(metric, color)) in self.metrics.iter().enumerate() {
+            let top = self.pos.1 + CHART_SPACING + index as f32 * (chart_height + CHART_SPACING);
+            self.draw_chart(allegro_data, &statistics.get_series(metric, self.length), metric, color, top, chart_height);
+        }
+    }
+
+    fn draw_chart(&self, allegro_data: &AllegroData, series: &[f64], metric: Metric, color: Color, top: f32, height: f32) {
+        let core = allegro_data.get_core();
+        let primitives = allegro_data.get_primitives_addon();
+        let left = self.pos.0 + PADDING;
+        let width = self.size.0 - 2.0 * PADDING;
+        let plot_top = top + LABEL_HEIGHT;
+        let plot_height = height - LABEL_HEIGHT;
+
+        let max = series.iter().cloned().fold(0.0, f64::max);
+        let label = match series.last() {
+            Some(value) => format!("{}: {:.1} (max {:.1})", metric.get_name(), value, max),
+            None => String::from(metric.get_name())
+        };
+        core.draw_text(allegro_data.get_std_font(), color, left, top, FontAlign::Left, &label);
+        primitives.draw_rectangle(left, plot_top, left + width, plot_top + plot_height, Color::from_rgb(0x50, 0x50, 0x50), 1.0);
+
+        if series.len() < 2 {
+            return;
+        }
+
+        //at most one segment per pixel, the chart fills from the left until length ticks are recorded
+        let step = (series.len() as f32 / width).ceil().max(1.0) as usize;
+        let x_scale = width / (self.length - 1) as f32;
+        let y_scale = match max > 0.0 {
+            true => plot_height / max as f32,
+            false => 0.0
+        };
+        let to_point = | index: usize | (left + index as f32 * x_scale, plot_top + plot_height - series[index] as f32 * y_scale);
+
+        let mut last = to_point(0);
+        for index in (step..series.len()).step_by(step).chain(Some(series.len() - 1)) {
+            let point = to_point(index);
+            primitives.draw_line(last.0, last.1, point.0, point.1, color, 1.0);
+            last = point;
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::time::Instant;
+use std::path::PathBuf;
+
+use simulator::Simulator;
+use frameexporter::FrameExporter;
+
+//the reports only show the latest tick, so without an export nothing older is kept
+const UNEXPORTED_HISTORY_LIMIT: usize = 1;
+
+type StopCondition = Box<dyn FnMut(&Simulator) -> bool>;
+
+//Runs a simulator without any display, until max_ticks is reached or the stop condition holds.
+//Without both it runs until the process is terminated.
+pub struct HeadlessRunner {
+    simulator: Simulator,
+    max_ticks: Option<u64>,
+    report_interval: u64,
+    stop_condition: Option<StopCondition>,
+    statistics_path: Option<PathBuf>,
+    frame_exporter: Option<FrameExporter>
+}
+
+impl HeadlessRunner {
+
+    pub fn new(simulator: Simulator) -> HeadlessRunner {
+        HeadlessRunner {
+            simulator,
+            max_ticks: None,
+            report_interval: 1000,
+            stop_condition: None,
+            statistics_path: None,
+            frame_exporter: None
+        }
+    }
+
+    pub fn max_ticks(mut self, max_ticks: u64) -> Self {
+        self.max_ticks = Some(max_ticks);
+        self
+    }
+
+    //a report interval of 0 disables the periodic statistics
+    pub fn report_interval(mut self, report_interval: u64) -> Self {
+        self.report_interval = report_interval;
+        self
+    }
+
+    pub fn stop_when<F>(mut self, condition: F) -> Self
+        where F: FnMut(&Simulator) -> bool + 'static {
+        self.stop_condition = Some(Box::new(condition));
+        self
+    }
+
+    //the statistics are saved there after the run, as json for a .json file and as csv otherwise
+    pub fn statistics_path(mut self, statistics_path: PathBuf) -> Self {
+        self.statistics_path = Some(statistics_path);
+        self
+    }
+
+    //renders the simulation into image files while running
+    pub fn export_frames(mut self, frame_exporter: FrameExporter) -> Self {
+        self.frame_exporter = Some(frame_exporter);
+        self
+    }
+
+    pub fn get_simulator(&self) -> &Simulator {
+        &self.simulator
+    }
+
+    pub fn into_simulator(self) -> Simulator {
+        self.simulator
+    }
+
+    //returns the number of ticks run, fails if frames or statistics can't be saved,
+    //without a statistics path the history is limited unless the simulator already has a limit
+    pub fn run(&mut self) -> Result<u64, String> {
+        let statistics = self.simulator.get_statistics_mut();
+        if self.statistics_path.is_none() && statistics.get_history_limit().is_none() {
+            statistics.set_history_limit(Some(UNEXPORTED_HISTORY_LIMIT));
+        }
+
+        let start_ticks = self.simulator.get_ticks();
+        let start_time = Instant::now();
+
+        loop {
+            if let Some(max_ticks) = self.max_ticks {
+                if self.simulator.get_ticks() - start_ticks >= max_ticks {
+                    break;
+                }
+            }
+
+            if let Some(ref mut condition) = self.stop_condition {
+                if condition(&self.simulator) {
+                    break;
+                }
+            }
+
+            self.simulator.cycle();
+
+            if let Some(ref mut frame_exporter) = self.frame_exporter {
+                frame_exporter.export(&self.simulator)?;
+            }
+
+            if self.report_interval > 0 && self.simulator.get_ticks() % self.report_interval == 0 {
+                self.print_report(start_ticks, start_time);
+            }
+        }
+
+        if let Some(ref path) = self.statistics_path {
+            self.simulator.get_statistics().save(path)?;
+        }
+
+        Ok(self.simulator.get_ticks() - start_ticks)
+    }
+
+    fn print_report(&self, start_ticks: u64, start_time: Instant) {
+        let statistics = match self.simulator.get_statistics().get_last() {
+            Some(statistics) => *statistics,
+            None => return
+        };
+
+        let elapsed = start_time.elapsed();
+        let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+        let tickrate = match seconds > 0.0 {
+            true => (self.simulator.get_ticks() - start_ticks) as f64 / seconds,
+            false => 0.0
+        };
+
+        println!("ticks: {} | bots: {} | food: {} | avg energy: {:.1} | max energy: {} | avg error: {} | ticks/s: {:.0}",
+                 statistics.tick,
+                 statistics.population,
+                 self.simulator.get_foods().borrow().len(),
+                 statistics.mean_energy,
+                 statistics.max_energy,
+                 statistics.mean_error.map_or(String::from("-"), | e | format!("{:.2e}", e)),
+                 tickrate);
+    }
+}
+
+#[test]
+fn test_headless_run() {
+    let sim = Simulator::with_seed((800, 600), 10, 7).unwrap();
+
+    let mut runner = HeadlessRunner::new(sim)
+        .max_ticks(500)
+        .report_interval(0)
+        .stop_when(| sim | sim.get_ticks() >= 300);
+
+    assert!(runner.run() == Ok(300));
+    assert!(runner.get_simulator().get_bots().borrow().len() >= 10);
+}
+
+#[test]
+fn test_headless_history() {
+    let sim = Simulator::with_seed((800, 600), 10, 7).unwrap();
+    let mut runner = HeadlessRunner::new(sim)
+        .max_ticks(200)
+        .report_interval(0);
+    runner.run().unwrap();
+    assert!(runner.get_simulator().get_statistics().len() == UNEXPORTED_HISTORY_LIMIT);
+
+    //an exported history keeps every tick
+    let path = ::std::env::temp_dir().join("neural_net_headless_history.csv");
+    let sim = Simulator::with_seed((800, 600), 10, 7).unwrap();
+    let mut runner = HeadlessRunner::new(sim)
+        .max_ticks(200)
+        .report_interval(0)
+        .statistics_path(path.clone());
+    runner.run().unwrap();
+    assert!(runner.get_simulator().get_statistics().len() == 200);
+    ::std::fs::remove_file(path).unwrap();
+}
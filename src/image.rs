@@ -0,0 +1,287 @@
+//Image files from rendered pixels, without any codec library.
+//PNG files are compressed with a single deflate block of the fixed huffman codes. Matches are only searched
+//at the distance of the previous byte, the previous pixel and the pixel above, which covers the large
+//areas of one color that rendered frames consist of.
+
+use std::fs::File;
+use std::io::{ Write, BufWriter };
+use std::path::Path;
+
+use raster::RasterRenderer;
+use serialization::io_error;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+//the farthest a match may reach back
+const WINDOW_SIZE: usize = 32768;
+//the first length of every length code from 257 on, and the number of extra bits
+const LENGTH_BASES: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA_BITS: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+//the first distance of every distance code, and the number of extra bits
+const DISTANCE_BASES: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769,
+                                   1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    //binary portable pixmap (P6)
+    Ppm
+}
+
+impl ImageFormat {
+
+    pub fn get_extension(&self) -> &'static str {
+        match *self {
+            ImageFormat::Png => "png",
+            ImageFormat::Ppm => "ppm"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<ImageFormat, String> {
+        match name {
+            "png" => Ok(ImageFormat::Png),
+            "ppm" => Ok(ImageFormat::Ppm),
+            _ => Err(format!("Unknown image format \"{}\"", name))
+        }
+    }
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xEDB8_8320,
+                _ => crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % MODULUS;
+        b = (b + a) % MODULUS;
+    }
+    (b << 16) | a
+}
+
+fn write_png_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> Result<(), String> {
+    let mut crc_data = kind.to_vec();
+    crc_data.extend_from_slice(data);
+
+    writer.write_all(&(data.len() as u32).to_be_bytes()).map_err(io_error)?;
+    writer.write_all(&crc_data).map_err(io_error)?;
+    writer.write_all(&crc32(&crc_data).to_be_bytes()).map_err(io_error)
+}
+
+//deflate packs bits starting at the least significant bit of every byte
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_count: u32
+}
+
+impl BitWriter {
+
+    fn new() -> BitWriter {
+        BitWriter {
+            bytes: Vec::new(),
+            bit_count: 0
+        }
+    }
+
+    //the lowest count bits of value, least significant first
+    fn write_bits(&mut self, value: u32, count: u32) {
+        for bit in 0..count {
+            if self.bit_count % 8 == 0 {
+                self.bytes.push(0);
+            }
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= (((value >> bit) & 1) as u8) << (self.bit_count % 8);
+            self.bit_count += 1;
+        }
+    }
+
+    //huffman codes are packed starting at their most significant bit
+    fn write_code(&mut self, code: u32, length: u32) {
+        for bit in (0..length).rev() {
+            self.write_bits(code >> bit, 1);
+        }
+    }
+
+    fn write_literal(&mut self, symbol: u16) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xC0 + symbol - 280, 8)
+        }
+    }
+
+    fn write_match(&mut self, length: usize, distance: usize) {
+        let code = LENGTH_BASES.iter().rposition(| &base | base as usize <= length).unwrap();
+        self.write_literal(257 + code as u16);
+        self.write_bits((length - LENGTH_BASES[code] as usize) as u32, LENGTH_EXTRA_BITS[code] as u32);
+
+        let code = DISTANCE_BASES.iter().rposition(| &base | base as usize <= distance).unwrap();
+        self.write_code(code as u32, 5);
+        self.write_bits((distance - DISTANCE_BASES[code] as usize) as u32, DISTANCE_EXTRA_BITS[code] as u32);
+    }
+}
+
+//the length of the match of the data at position with the data distance bytes before
+fn get_match_length(data: &[u8], position: usize, distance: usize) -> usize {
+    let max_length = MAX_MATCH.min(data.len() - position);
+    (0..max_length).take_while(| &i | data[position + i] == data[position + i - distance]).count()
+}
+
+//a zlib stream of one fixed huffman block, bytes_per_pixel and row_length give the distances searched for matches
+fn get_zlib_compressed(data: &[u8], bytes_per_pixel: usize, row_length: usize) -> Vec<u8> {
+    let distances = [1, bytes_per_pixel, row_length];
+    let mut writer = BitWriter::new();
+    //final block with the fixed codes
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let mut position = 0;
+    while position < data.len() {
+        let (length, distance) = distances.iter()
+            .filter(| &&distance | distance > 0 && distance <= position && distance <= WINDOW_SIZE)
+            .map(| &distance | (get_match_length(data, position, distance), distance))
+            .fold((0, 0), | best, candidate | match candidate.0 > best.0 {
+                true => candidate,
+                false => best
+            });
+
+        if length >= MIN_MATCH {
+            writer.write_match(length, distance);
+            position += length;
+        } else {
+            writer.write_literal(data[position] as u16);
+            position += 1;
+        }
+    }
+    writer.write_literal(256);
+
+    //deflate with the default window
+    let mut stream = vec![0x78, 0x01];
+    stream.extend_from_slice(&writer.bytes);
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+pub fn write_png<W: Write>(raster: &RasterRenderer, writer: &mut W) -> Result<(), String> {
+    let (width, height) = (raster.get_width(), raster.get_height());
+    let pixels = raster.get_rgb_bytes();
+
+    //every row starts with its filter type, 0 is none
+    let mut scanlines = Vec::with_capacity(height * (width * 3 + 1));
+    for row in pixels.chunks(width * 3) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    //8 bit rgb, default compression and filtering, no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    writer.write_all(&PNG_SIGNATURE).map_err(io_error)?;
+    write_png_chunk(writer, b"IHDR", &header)?;
+    write_png_chunk(writer, b"IDAT", &get_zlib_compressed(&scanlines, 3, width * 3 + 1))?;
+    write_png_chunk(writer, b"IEND", &[])
+}
+
+pub fn write_ppm<W: Write>(raster: &RasterRenderer, writer: &mut W) -> Result<(), String> {
+    writer.write_all(format!("P6\n{} {}\n255\n", raster.get_width(), raster.get_height()).as_bytes()).map_err(io_error)?;
+    writer.write_all(&raster.get_rgb_bytes()).map_err(io_error)
+}
+
+pub fn save_image(raster: &RasterRenderer, format: ImageFormat, path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(io_error)?;
+    let mut writer = BufWriter::new(file);
+    match format {
+        ImageFormat::Png => write_png(raster, &mut writer),
+        ImageFormat::Ppm => write_ppm(raster, &mut writer)
+    }?;
+    writer.flush().map_err(io_error)
+}
+
+//decodes with an independent inflate implementation, which also checks the adler32 of the zlib stream
+#[cfg(test)]
+fn inflate(data: &[u8]) -> Vec<u8> {
+    ::miniz_oxide::inflate::decompress_to_vec_zlib(data).unwrap()
+}
+
+#[test]
+fn test_zlib_compression() {
+    use rand::Rng;
+    use random::create_rng;
+
+    //literals only, runs longer than the longest match and repeats at all three distances
+    let mut rng = create_rng(3);
+    let noise: Vec<u8> = (0..5000).map(| _ | rng.gen::<u8>()).collect();
+    let mut mixed = Vec::new();
+    for row in 0..40 {
+        mixed.push(0);
+        for pixel in 0..50 {
+            mixed.extend_from_slice(&[(pixel / 7) as u8, (row / 3) as u8, 200]);
+        }
+    }
+    for data in [Vec::new(), vec![7], noise, vec![0; 1000], mixed].iter() {
+        assert!(inflate(&get_zlib_compressed(data, 3, 151)) == *data);
+    }
+}
+
+#[test]
+fn test_image_encoding() {
+    use color::Color;
+    use renderer::Renderer;
+
+    assert!(crc32(b"IEND") == 0xAE42_6082);
+    assert!(adler32(b"Wikipedia") == 0x11E6_0398);
+
+    //large enough for matches reaching back a whole row
+    let mut raster = RasterRenderer::new(200, 150);
+    raster.clear(Color::from_rgb(10, 20, 30));
+    raster.draw_filled_circle((100.0, 75.0), 30.0, Color::from_rgb(200, 100, 0));
+
+    let mut ppm = Vec::new();
+    write_ppm(&raster, &mut ppm).unwrap();
+    assert!(ppm.starts_with(b"P6\n200 150\n255\n") && ppm.len() == 15 + 200 * 150 * 3);
+
+    let mut png = Vec::new();
+    write_png(&raster, &mut png).unwrap();
+    assert!(png[..8] == PNG_SIGNATURE);
+
+    //walk the chunks, check their crcs and collect the image data
+    let mut position = 8;
+    let mut kinds = Vec::new();
+    let mut idat = Vec::new();
+    while position < png.len() {
+        let length = u32::from_be_bytes([png[position], png[position + 1], png[position + 2], png[position + 3]]) as usize;
+        let chunk = &png[position + 4..position + 8 + length];
+        let crc = &png[position + 8 + length..position + 12 + length];
+        assert!(crc32(chunk).to_be_bytes() == [crc[0], crc[1], crc[2], crc[3]]);
+        kinds.push(String::from_utf8(chunk[..4].to_vec()).unwrap());
+        if &chunk[..4] == b"IDAT" {
+            idat.extend_from_slice(&chunk[4..]);
+        }
+        position += 12 + length;
+    }
+    assert!(kinds == ["IHDR", "IDAT", "IEND"]);
+
+    let scanlines = inflate(&idat);
+    //the flat areas compress well
+    assert!(png.len() < ppm.len() / 10);
+    let pixels: Vec<u8> = scanlines.chunks(200 * 3 + 1).flat_map(| row | row[1..].to_vec()).collect();
+    assert!(pixels == raster.get_rgb_bytes());
+}
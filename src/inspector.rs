@@ -0,0 +1,97 @@
+use allegro::Color;
+use allegro_font::{ FontDrawing, FontAlign };
+
+use allegrodata::{ AllegroData, AllegroRenderer };
+use bot::Bot;
+use netdiagram::NetDiagram;
+
+const LINE_HEIGHT: f32 = 11.0;
+const PADDING: f32 = 4.0;
+//values of a layer are wrapped into lines of this many
+const VALUES_PER_LINE: usize = 6;
+
+fn format_values(values: &[f64]) -> Vec<String> {
+    if values.is_empty() {
+        return vec![String::from("  -")];
+    }
+    values.chunks(VALUES_PER_LINE)
+        .map(| chunk | format!("  {}", chunk.iter().map(| e | format!("{:7.3}", e)).collect::<Vec<_>>().join(" ")))
+        .collect()
+}
+
+//A panel with the state, sensor inputs and network of one bot.
+//The text is on the left, the diagram of the network fills the rest of the panel.
+pub struct Inspector {
+    pos: (f32, f32),
+    size: (f32, f32),
+    text_width: f32
+}
+
+impl Inspector {
+
+    pub fn new(pos: (f32, f32), size: (f32, f32), text_width: f32) -> Inspector {
+        assert!(text_width <= size.0);
+
+        Inspector {
+            pos,
+            size,
+            text_width
+        }
+    }
+
+    pub fn get_lines(bot: &Bot) -> Vec<String> {
+        let pos = bot.get_pos();
+        let mut lines = vec![
+            format!("bot {}", bot.get_id()),
+            format!("pos: {:.1} {:.1}  rotation: {:.2}", pos.0, pos.1, bot.get_rotation()),
+            format!("energy: {}  age: {}  eaten: {}", bot.get_energy(), bot.get_age(), bot.get_eaten_energy()),
+            format!("velocity: {:.2}  error: {}", bot.get_velocity(), bot.get_training_error().map_or(String::from("-"), | e | format!("{:.2e}", e))),
+            String::new(),
+            String::from("sensors")
+        ];
+
+        let mut offset = 0;
+        for sensor in bot.get_sensors() {
+            let end = (offset + sensor.get_size()).min(bot.get_inputs().len());
+            lines.push(format!(" {}", sensor.get_name()));
+            lines.extend(format_values(&bot.get_inputs()[offset.min(end)..end]));
+            offset += sensor.get_size();
+        }
+
+        let nn = bot.get_net();
+        for layer in 0..nn.get_layer_count() {
+            let name = match layer {
+                0 => String::from("normalized input"),
+                _ if layer == nn.get_layer_count() - 1 => String::from("output"),
+                _ => format!("hidden layer {}", layer)
+            };
+            lines.push(String::new());
+            lines.push(format!("{} ({:?})", name, nn.get_activation(layer)));
+            lines.extend(format_values(nn.get_layer_output(layer)));
+        }
+
+        lines.push(String::new());
+        lines.push(String::from("actions"));
+        lines.extend(format_values(bot.get_actions()));
+        lines
+    }
+
+    pub fn draw(&self, allegro_data: &AllegroData, bot: &Bot) {
+        let core = allegro_data.get_core();
+        allegro_data.get_primitives_addon().draw_filled_rectangle(self.pos.0, self.pos.1, self.pos.0 + self.text_width, self.pos.1 + self.size.1, Color::from_rgba(0, 0, 0, 200));
+
+        let max_lines = ((self.size.1 - 2.0 * PADDING) / LINE_HEIGHT) as usize;
+        for (index, line) in Inspector::get_lines(bot).iter().take(max_lines).enumerate() {
+            core.draw_text(allegro_data.get_std_font(), allegro_data.get_white(), self.pos.0 + PADDING, self.pos.1 + PADDING + index as f32 * LINE_HEIGHT, FontAlign::Left, line);
+        }
+
+        let diagram_size = (self.size.0 - self.text_width, self.size.1 * 0.4);
+        if diagram_size.0 > 0.0 {
+            let diagram = NetDiagram::new(bot.get_net(), diagram_size);
+            //the inspector is drawn onto the backbuffer of the display
+            let display = allegro_data.get_display();
+            let target_size = (display.get_width() as f32, display.get_height() as f32);
+            diagram.draw(&mut AllegroRenderer::new(allegro_data, target_size), (self.pos.0 + self.text_width, self.pos.1 + self.size.1 - diagram_size.1));
+        }
+    }
+}
@@ -0,0 +1,53 @@
+
+#[cfg(feature = "gui")]
+extern crate allegro;
+#[cfg(feature = "gui")]
+extern crate allegro_primitives;
+#[cfg(feature = "gui")]
+extern crate allegro_font;
+extern crate rand;
+#[cfg(test)]
+extern crate miniz_oxide;
+
+pub mod neuralnet;
+pub mod activation;
+pub mod loss;
+pub mod optimizer;
+pub mod normalization;
+pub mod serialization;
+pub mod random;
+pub mod vectorn;
+pub mod trainingset;
+pub mod simulator;
+pub mod spatialgrid;
+pub mod snapshot;
+pub mod replay;
+pub mod headless;
+pub mod benchmark;
+pub mod statistics;
+pub mod evolution;
+pub mod reinforcement;
+pub mod teacher;
+pub mod bot;
+pub mod food;
+pub mod color;
+pub mod environment;
+pub mod sensor;
+pub mod utility;
+pub mod ray;
+pub mod netdiagram;
+pub mod renderer;
+pub mod svg;
+pub mod raster;
+pub mod image;
+pub mod frameexporter;
+pub mod cli;
+
+#[cfg(feature = "gui")]
+pub mod allegrodata;
+#[cfg(feature = "gui")]
+pub mod window;
+#[cfg(feature = "gui")]
+pub mod graphpanel;
+#[cfg(feature = "gui")]
+pub mod inspector;
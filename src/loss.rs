@@ -0,0 +1,175 @@
+use activation::Activation;
+
+//keeps the logarithms of the cross entropy losses finite
+const EPSILON: f64 = 1e-12;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Loss {
+    SquaredError,
+    BinaryCrossEntropy,
+    CategoricalCrossEntropy,
+    Huber(f64)
+}
+
+fn clamp_probability(value: f64) -> f64 {
+    value.clamp(EPSILON, 1.0 - EPSILON)
+}
+
+impl Loss {
+
+    pub fn get_name(&self) -> String {
+        match *self {
+            Loss::SquaredError => String::from("squared_error"),
+            Loss::BinaryCrossEntropy => String::from("binary_cross_entropy"),
+            Loss::CategoricalCrossEntropy => String::from("categorical_cross_entropy"),
+            Loss::Huber(delta) => format!("huber:{}", delta)
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Loss, String> {
+        match name {
+            "squared_error" => Ok(Loss::SquaredError),
+            "binary_cross_entropy" => Ok(Loss::BinaryCrossEntropy),
+            "categorical_cross_entropy" => Ok(Loss::CategoricalCrossEntropy),
+            _ if name.starts_with("huber:") => match name["huber:".len()..].parse::<f64>() {
+                Ok(delta) if delta.is_finite() && delta > 0.0 => Ok(Loss::Huber(delta)),
+                _ => Err(format!("Invalid huber delta in \"{}\", expected a finite delta greater than 0", name))
+            },
+            _ => Err(format!("Unknown loss \"{}\"", name))
+        }
+    }
+
+    pub fn error(&self, output: &[f64], target: &[f64]) -> f64 {
+        assert_eq!(output.len(), target.len());
+
+        let mut sum = 0f64;
+        for (&out, &tar) in output.iter().zip(target.iter()) {
+            sum += match *self {
+                Loss::SquaredError => 0.5 * (tar - out).powi(2),
+                Loss::BinaryCrossEntropy => {
+                    let out = clamp_probability(out);
+                    -(tar * out.ln() + (1.0 - tar) * (1.0 - out).ln())
+                },
+                Loss::CategoricalCrossEntropy => -tar * clamp_probability(out).ln(),
+                Loss::Huber(delta) => {
+                    let diff = (out - tar).abs();
+                    match diff <= delta {
+                        true => 0.5 * diff * diff,
+                        false => delta * (diff - 0.5 * delta)
+                    }
+                }
+            };
+        }
+        sum
+    }
+
+    //gradient of the error with respect to every output unit
+    pub fn gradient(&self, output: &[f64], target: &[f64], gradient: &mut [f64]) {
+        assert_eq!(output.len(), target.len());
+        assert_eq!(output.len(), gradient.len());
+
+        for unit in 0..output.len() {
+            let (out, tar) = (output[unit], target[unit]);
+            gradient[unit] = match *self {
+                Loss::SquaredError => out - tar,
+                Loss::BinaryCrossEntropy => {
+                    let out = clamp_probability(out);
+                    (out - tar) / (out * (1.0 - out))
+                },
+                Loss::CategoricalCrossEntropy => -tar / clamp_probability(out),
+                Loss::Huber(delta) => (out - tar).max(-delta).min(delta)
+            };
+        }
+    }
+
+    //gradient of the error with respect to the inputs of the output layer
+    //cross entropy paired with its matching activation collapses to output - target,
+    //which avoids dividing by saturated outputs
+    pub fn output_delta(&self, activation: Activation, input: &[f64], output: &[f64], target: &[f64], delta: &mut [f64]) {
+        match (*self, activation) {
+            (Loss::BinaryCrossEntropy, Activation::Sigmoid) |
+            (Loss::CategoricalCrossEntropy, Activation::Softmax) => {
+                for unit in 0..output.len() {
+                    delta[unit] = output[unit] - target[unit];
+                }
+            },
+            _ => {
+                self.gradient(output, target, delta);
+                activation.backpropagate(input, output, delta);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+const LOSSES: [Loss; 4] = [Loss::SquaredError, Loss::BinaryCrossEntropy, Loss::CategoricalCrossEntropy, Loss::Huber(0.3)];
+
+//central difference of the error with respect to every value of point
+#[cfg(test)]
+fn numeric_gradient<F: Fn(&[f64]) -> f64>(error: F, point: &[f64]) -> Vec<f64> {
+    let step = 1e-6;
+    (0..point.len()).map(| i | {
+        let (mut above, mut below) = (point.to_vec(), point.to_vec());
+        above[i] += step;
+        below[i] -= step;
+        (error(&above) - error(&below)) / (2.0 * step)
+    }).collect()
+}
+
+#[test]
+fn test_loss_gradient() {
+    //the huber differences lie on both sides of its delta
+    let output = [0.2, 0.7, 0.1];
+    let target = [0.0, 1.0, 0.0];
+
+    for loss in LOSSES.iter() {
+        let mut gradient = [0.0; 3];
+        loss.gradient(&output, &target, &mut gradient);
+
+        let numeric = numeric_gradient(| output | loss.error(output, &target), &output);
+        for unit in 0..output.len() {
+            assert!((numeric[unit] - gradient[unit]).abs() < 1e-5, "{:?} unit {}", loss, unit);
+        }
+    }
+
+    assert!((Loss::SquaredError.error(&output, &target) - 0.5 * (0.04 + 0.09 + 0.01)).abs() < 1e-12);
+    assert!((Loss::CategoricalCrossEntropy.error(&output, &target) + 0.7f64.ln()).abs() < 1e-12);
+    assert!((Loss::Huber(0.3).error(&output, &target) - (0.5 * 0.04 + 0.3 * (0.3 - 0.15) + 0.5 * 0.01)).abs() < 1e-12);
+}
+
+#[test]
+fn test_output_delta() {
+    let input = [0.5, -1.0, 2.0];
+    let target = [0.0, 1.0, 0.0];
+    //the first two take the output - target shortcut
+    let pairs = [(Loss::BinaryCrossEntropy, Activation::Sigmoid),
+                 (Loss::CategoricalCrossEntropy, Activation::Softmax),
+                 (Loss::SquaredError, Activation::Tanh),
+                 (Loss::Huber(0.3), Activation::Sigmoid)];
+
+    for &(loss, activation) in pairs.iter() {
+        let mut output = [0.0; 3];
+        activation.activate(&input, &mut output);
+        let mut delta = [0.0; 3];
+        loss.output_delta(activation, &input, &output, &target, &mut delta);
+
+        let numeric = numeric_gradient(| input | {
+            let mut output = [0.0; 3];
+            activation.activate(input, &mut output);
+            loss.error(&output, &target)
+        }, &input);
+        for unit in 0..input.len() {
+            assert!((numeric[unit] - delta[unit]).abs() < 1e-5, "{:?} with {:?} unit {}", loss, activation, unit);
+        }
+    }
+}
+
+#[test]
+fn test_loss_from_name() {
+    for loss in LOSSES.iter() {
+        assert!(Loss::from_name(&loss.get_name()) == Ok(*loss));
+    }
+    for name in ["huber:NaN", "huber:inf", "huber:-1", "huber:0", "huber:", "hinge"].iter() {
+        assert!(Loss::from_name(name).is_err(), "{}", name);
+    }
+}
@@ -1,102 +1,507 @@
-#![allow(dead_code)]
-
-extern crate allegro;
-extern crate allegro_primitives;
-extern crate allegro_font;
-extern crate rand;
-
-mod neuralnet;
-mod vectorn;
-mod trainingset;
-mod simulator;
-mod allegrodata;
-mod bot;
-mod utility;
-mod ray;
-mod window;
-mod environment;
-mod food;
-
-use simulator::Simulator;
-use window::{ WindowBuilder };
+
+extern crate neural_net;
+
+use std::env;
+use std::process;
+use std::path::{ Path, PathBuf };
+
+use neural_net::cli::{ OptionSpec, Arguments, parse_size, parse_point, parse_list, format_help };
+use neural_net::simulator::Simulator;
+use neural_net::headless::HeadlessRunner;
+use neural_net::benchmark::print_benchmark;
+use neural_net::replay::Recorder;
+use neural_net::frameexporter::FrameExporter;
+use neural_net::image::ImageFormat;
+use neural_net::evolution::{ Evolution, Selection };
+use neural_net::reinforcement::LearningMode;
+use neural_net::sensor::Sensor;
+use neural_net::neuralnet::NeuralNet;
+use neural_net::trainingset::Trainingset;
+use neural_net::activation::Activation;
+use neural_net::loss::Loss;
+use neural_net::optimizer;
+use neural_net::random::{ create_rng, generate_seed };
+#[cfg(feature = "gui")]
+use neural_net::window::WindowBuilder;
+#[cfg(feature = "gui")]
+use neural_net::replay::Replay;
+
+const SCREEN_SIZE: (i32, i32) = (1024, 768);
+const FIELD_SIZE: (i32, i32) = (SCREEN_SIZE.0 * 2, SCREEN_SIZE.1 * 2);
+const BOT_COUNT: u32 = 40;
+const FOOD_COUNT: u32 = 50;
+const MUTATION_RATE: f64 = 0.1;
+#[cfg(feature = "gui")]
+const GUI_TICK_RATE: i32 = 60;
+#[cfg(feature = "gui")]
+const REPLAY_TICK_RATE: i32 = 30;
+const REPORT_INTERVAL: u64 = 1000;
+const BENCHMARK_BOT_COUNTS: [u32; 5] = [100, 500, 1000, 2000, 5000];
+const BENCHMARK_TICKS: u64 = 100;
+const FRAME_SIZE: (usize, usize) = (1024, 768);
+const TRAINING_RUNS: u32 = 100000;
+const TRAINING_REPORT_INTERVAL: u32 = 1000;
+const LEARNING_RATE: f64 = 0.05;
+const TARGET_ERROR: f64 = 1e-6;
+
+struct Command {
+    name: &'static str,
+    //the names of the positional arguments, all of them are required
+    arguments: &'static [&'static str],
+    description: &'static str,
+    //whether the command takes the options which configure a simulation
+    simulation: bool,
+    options: &'static [OptionSpec],
+    run: fn(&Arguments) -> Result<(), String>
+}
+
+impl Command {
+
+    fn get_usage(&self) -> String {
+        let mut usage = format!("neural_net {}", self.name);
+        for argument in self.arguments {
+            usage += &format!(" <{}>", argument);
+        }
+        usage + " [options]"
+    }
+
+    fn get_options(&self) -> Vec<OptionSpec> {
+        let mut options = self.options.to_vec();
+        if self.simulation {
+            options.extend_from_slice(SIMULATION_OPTIONS);
+        }
+        options
+    }
+
+    fn get_help(&self) -> String {
+        format_help(&self.get_usage(), self.description, &self.get_options())
+    }
+}
+
+const SIMULATION_OPTIONS: &[OptionSpec] = &[
+    OptionSpec { name: "field", value: Some("WxH"), help: "size of the field (default 2048x1536)" },
+    OptionSpec { name: "bots", value: Some("COUNT"), help: "least number of bots, dead bots are replaced (default 40)" },
+    OptionSpec { name: "food", value: Some("COUNT"), help: "number of food items (default 50)" },
+    OptionSpec { name: "seed", value: Some("SEED"), help: "seed of the simulation, random by default" },
+    OptionSpec { name: "evolution", value: Some("SELECTION"), help: "breed new bots, tournament:<n>, roulette or elitism:<n>" },
+    OptionSpec { name: "mutation-rate", value: Some("RATE"), help: "chance of a bred parameter to mutate (default 0.1)" },
+    OptionSpec { name: "learning", value: Some("MODE"), help: "supervised or qlearning (default supervised)" },
+    OptionSpec { name: "sensors", value: Some("LIST"), help: "comma separated sensors, e.g. rays:8,nearest_bot,energy" },
+    OptionSpec { name: "load", value: Some("SNAPSHOT"), help: "continue from a snapshot of a field of the same size" }
+];
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "gui",
+        arguments: &[],
+        description: "Runs a simulation in a window. This is the default command.",
+        simulation: true,
+        options: &[
+            OptionSpec { name: "screen", value: Some("WxH"), help: "size of the window (default 1024x768)" },
+            OptionSpec { name: "tickrate", value: Some("TICKS"), help: "simulated ticks per second (default 60)" },
+            OptionSpec { name: "snapshot", value: Some("FILE"), help: "file saved with F5 and loaded with F9 (default snapshot.txt)" }
+        ],
+        run: run_gui
+    },
+    Command {
+        name: "headless",
+        arguments: &[],
+        description: "Runs a simulation without a window and prints a report now and then.",
+        simulation: true,
+        options: &[
+            OptionSpec { name: "ticks", value: Some("TICKS"), help: "stop after this many ticks, runs until interrupted by default" },
+            OptionSpec { name: "report", value: Some("TICKS"), help: "ticks between two reports, 0 for none (default 1000)" },
+            OptionSpec { name: "record", value: Some("FILE"), help: "record a replay" },
+            OptionSpec { name: "statistics", value: Some("FILE"), help: "save the statistics of every tick, .json or .csv" },
+            OptionSpec { name: "save", value: Some("SNAPSHOT"), help: "save a snapshot at the end" },
+            OptionSpec { name: "frames", value: Some("DIR"), help: "render frames into the directory" },
+            OptionSpec { name: "frame-interval", value: Some("TICKS"), help: "ticks between two frames (default 1)" },
+            OptionSpec { name: "frame-size", value: Some("WxH"), help: "size of the frames (default 1024x768)" },
+            OptionSpec { name: "frame-format", value: Some("FORMAT"), help: "png or ppm (default png)" },
+            OptionSpec { name: "camera", value: Some("X,Y"), help: "field position in the middle of the frames (default the middle of the field)" },
+            OptionSpec { name: "zoom", value: Some("ZOOM"), help: "zoom on the camera position, 1 fits the whole field (default 1)" }
+        ],
+        run: run_headless
+    },
+    Command {
+        name: "replay",
+        arguments: &["REPLAY"],
+        description: "Plays a recorded replay back in a window.",
+        simulation: false,
+        options: &[
+            OptionSpec { name: "screen", value: Some("WxH"), help: "size of the window (default 1024x768)" },
+            OptionSpec { name: "tickrate", value: Some("TICKS"), help: "replayed ticks per second (default 30)" }
+        ],
+        run: run_replay
+    },
+    Command {
+        name: "train",
+        arguments: &["DATASET"],
+        description: "Trains a network on a dataset, one set per line with the inputs and targets separated by \"|\".",
+        simulation: false,
+        options: &[
+            OptionSpec { name: "hidden", value: Some("LIST"), help: "comma separated unit counts of the hidden layers (default 8)" },
+            OptionSpec { name: "load", value: Some("NETWORK"), help: "continue training a saved network, excludes --hidden and --seed" },
+            OptionSpec { name: "output", value: Some("NETWORK"), help: "save the trained network, in binary if it ends with .bin" },
+            OptionSpec { name: "runs", value: Some("RUNS"), help: "most passes over the dataset (default 100000)" },
+            OptionSpec { name: "report", value: Some("RUNS"), help: "runs between two reports (default 1000)" },
+            OptionSpec { name: "target-error", value: Some("ERROR"), help: "stop once the average error is below (default 1e-6)" },
+            OptionSpec { name: "learning-rate", value: Some("RATE"), help: "(default 0.05)" },
+            OptionSpec { name: "batch-size", value: Some("SETS"), help: "sets per update (default 1)" },
+            OptionSpec { name: "optimizer", value: Some("NAME"), help: "sgd, momentum[:m], nesterov[:m], rmsprop[:decay] or adam (default adam)" },
+            OptionSpec { name: "hidden-activation", value: Some("NAME"), help: "e.g. sigmoid, tanh, relu (default sigmoid)" },
+            OptionSpec { name: "output-activation", value: Some("NAME"), help: "(default sigmoid)" },
+            OptionSpec { name: "loss", value: Some("NAME"), help: "e.g. squared_error, binary_cross_entropy (default squared_error)" },
+            OptionSpec { name: "seed", value: Some("SEED"), help: "seed of the initial weights, random by default" }
+        ],
+        run: run_train
+    },
+    Command {
+        name: "eval",
+        arguments: &["NETWORK", "DATASET"],
+        description: "Prints the average error of a saved network on a dataset.",
+        simulation: false,
+        options: &[
+            OptionSpec { name: "verbose", value: None, help: "print the output for every set" }
+        ],
+        run: run_eval
+    },
+    Command {
+        name: "bench",
+        arguments: &[],
+        description: "Measures the simulation speed for several population sizes.",
+        simulation: false,
+        options: &[
+            OptionSpec { name: "ticks", value: Some("TICKS"), help: "ticks per population size (default 100)" },
+            OptionSpec { name: "bots", value: Some("LIST"), help: "comma separated population sizes (default 100,500,1000,2000,5000)" },
+            OptionSpec { name: "seed", value: Some("SEED"), help: "(default 0)" }
+        ],
+        run: run_bench
+    }
+];
+
+fn find_command(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(| command | command.name == name)
+}
+
+fn get_general_help() -> String {
+    let mut help = String::from("usage: neural_net [command] [options]\n\ncommands:\n");
+    for command in COMMANDS {
+        help += &format!("  {:10}{}\n", command.name, command.description);
+    }
+    help + "\nneural_net help <command> or neural_net <command> --help shows the options of a command\n"
+}
 
 fn main() {
-    const SCREEN_SIZE: (i32, i32) = (1024, 768);
-    const FIELD_SIZE: (i32, i32) = (SCREEN_SIZE.0 * 2, SCREEN_SIZE.1 * 2);
-    const BOT_COUNT: u32 = 40;
-    const TICK_RATE: i32 = 30;
-
-    let sim = match Simulator::new(FIELD_SIZE, BOT_COUNT) {
-        Ok(sim) => sim,
-        Err(e) => {
-            println!("ERROR: {:?}", e);
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    //without a command name the window is opened
+    let (command, args) = match args.first().map(| arg | arg.as_str()) {
+        Some("help") | Some("--help") | Some("-h") => {
+            match args.get(1).and_then(| name | find_command(name)) {
+                Some(command) => print!("{}", command.get_help()),
+                None => print!("{}", get_general_help())
+            }
             return;
-        }
+        },
+        Some(name) if !name.starts_with('-') => match find_command(name) {
+            Some(command) => (command, &args[1..]),
+            None => exit_with_error(&format!("Unknown command \"{}\"\n\n{}", name, get_general_help()))
+        },
+        _ => (&COMMANDS[0], &args[..])
+    };
+
+    let arguments = match Arguments::parse(args, &command.get_options()) {
+        Ok(arguments) => arguments,
+        Err(e) => exit_with_error(&format!("{}\n\n{}", e, command.get_help()))
     };
 
-    let wnd = WindowBuilder::new(SCREEN_SIZE)
+    if arguments.has_flag("help") {
+        print!("{}", command.get_help());
+        return;
+    }
+
+    if arguments.get_positional().len() != command.arguments.len() {
+        exit_with_error(&format!("Expected {} argument(s), got {}\n\n{}", command.arguments.len(), arguments.get_positional().len(), command.get_help()));
+    }
+
+    if let Err(e) = (command.run)(&arguments) {
+        exit_with_error(&e);
+    }
+}
+
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1)
+}
+
+//a simulation configured by SIMULATION_OPTIONS
+fn create_simulator(args: &Arguments) -> Result<Simulator, String> {
+    let field_size = args.get_with("field", parse_size)?.unwrap_or(FIELD_SIZE);
+    if field_size.0 <= 0 || field_size.1 <= 0 {
+        return Err(String::from("The field size must be positive"));
+    }
+    let seed = args.get("seed")?.unwrap_or_else(generate_seed);
+    println!("seed: {}", seed);
+
+    //bots are spawned once everything which applies to new bots is set
+    let mut sim = Simulator::with_seed(field_size, 0, seed)?;
+
+    if let Some(sensors) = args.get_with("sensors", | list | parse_list(list, Sensor::from_name))? {
+        sim.set_sensors(sensors);
+    }
+
+    if let Some(learning_mode) = args.get_with("learning", LearningMode::from_name)? {
+        sim.set_learning_mode(learning_mode);
+    }
+
+    let mutation_rate = args.get_or("mutation-rate", MUTATION_RATE)?;
+    if let Some(selection) = args.get_with("evolution", Selection::from_name)? {
+        if !(0.0..=1.0).contains(&mutation_rate) {
+            return Err(String::from("The mutation rate must be between 0 and 1"));
+        }
+        sim.set_evolution(Some(Evolution::new(selection, mutation_rate)));
+    }
+
+    sim.set_food_count(args.get_or("food", FOOD_COUNT)?);
+    sim.set_bot_count(args.get_or("bots", BOT_COUNT)?);
+
+    if let Some(path) = args.get_str("load") {
+        sim.load_snapshot(Path::new(path))?;
+        println!("loaded {}", path);
+    }
+    Ok(sim)
+}
+
+#[cfg(feature = "gui")]
+fn run_gui(args: &Arguments) -> Result<(), String> {
+    let screen_size = args.get_with("screen", parse_size)?.unwrap_or(SCREEN_SIZE);
+    let tickrate = args.get_or("tickrate", GUI_TICK_RATE)?;
+    let sim = create_simulator(args)?;
+
+    let mut builder = WindowBuilder::new(screen_size)
         .frame_pos((5.0, 25.0))
-        .frame_size((SCREEN_SIZE.0 as f32 * 0.9, SCREEN_SIZE.1 as f32 * 0.9))
-        .tickrate(60)
+        .frame_size((screen_size.0 as f32 * 0.9, screen_size.1 as f32 * 0.9))
+        .tickrate(tickrate)
         .redraw_rate(60)
-        .simulator(sim)
-        .finish();
+        .simulator(sim);
 
-    match wnd {
-        Ok(mut wnd) => wnd.mainloop(),
-        Err(e) => {
-            println!("ERROR: {:?}", e);
-            return;
+    if let Some(path) = args.get_str("snapshot") {
+        builder = builder.snapshot_path(PathBuf::from(path));
+    }
+
+    let mut wnd = builder.finish()?;
+    wnd.mainloop();
+    Ok(())
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_gui(_args: &Arguments) -> Result<(), String> {
+    Err(String::from("built without the gui feature, use the headless command or rebuild with --features gui"))
+}
+
+#[cfg(feature = "gui")]
+fn run_replay(args: &Arguments) -> Result<(), String> {
+    let screen_size = args.get_with("screen", parse_size)?.unwrap_or(SCREEN_SIZE);
+    let tickrate = args.get_or("tickrate", REPLAY_TICK_RATE)?;
+    let replay = Replay::load(Path::new(&args.get_positional()[0]))?;
+
+    let mut wnd = WindowBuilder::new(screen_size)
+        .frame_pos((5.0, 25.0))
+        .frame_size((screen_size.0 as f32 * 0.9, screen_size.1 as f32 * 0.9))
+        .tickrate(tickrate)
+        .redraw_rate(60)
+        .replay(replay)
+        .finish()?;
+
+    wnd.mainloop();
+    Ok(())
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_replay(_args: &Arguments) -> Result<(), String> {
+    Err(String::from("built without the gui feature, replays need the window"))
+}
+
+fn run_headless(args: &Arguments) -> Result<(), String> {
+    let mut sim = create_simulator(args)?;
+
+    if let Some(path) = args.get_str("record") {
+        let recorder = Recorder::create(Path::new(path), sim.get_field_size())?;
+        sim.start_recording(recorder);
+        println!("recording to {}", path);
+    }
+
+    let mut runner = HeadlessRunner::new(sim)
+        .report_interval(args.get_or("report", REPORT_INTERVAL)?);
+
+    if let Some(ticks) = args.get("ticks")? {
+        runner = runner.max_ticks(ticks);
+    }
+
+    if let Some(path) = args.get_str("statistics") {
+        runner = runner.statistics_path(PathBuf::from(path));
+    }
+
+    if let Some(directory) = args.get_str("frames") {
+        let size = args.get_with("frame-size", parse_size)?.unwrap_or(FRAME_SIZE);
+        let interval = args.get_or("frame-interval", 1)?;
+        let zoom = args.get_or("zoom", 1.0)?;
+        if size.0 == 0 || size.1 == 0 || interval == 0 || zoom <= 0.0 {
+            return Err(String::from("The frame size, frame interval and zoom must be positive"));
         }
-    };
 
+        let mut exporter = FrameExporter::new(PathBuf::from(directory), size)
+            .interval(interval)
+            .format(args.get_with("frame-format", ImageFormat::from_name)?.unwrap_or(ImageFormat::Png));
+        exporter = match args.get_with("camera", parse_point)? {
+            Some(center) => exporter.camera(center, zoom),
+            None => exporter.zoom(zoom)
+        };
+        runner = runner.export_frames(exporter);
+    }
+
+    let ticks_run = runner.run()?;
+    println!("finished after {} ticks", ticks_run);
+
+    let mut sim = runner.into_simulator();
+    if let Some(path) = args.get_str("save") {
+        sim.save_snapshot(Path::new(path))?;
+        println!("saved {}", path);
+    }
+    sim.stop_recording()
 }
 
-/*let mut nn = NeuralNet::new(4, 4);
-let mut trainingset = Trainingset::new();
-let mut learning_rate: f64 = 2.0;
-let cycles = 100;
-let runs_per_cycle = 1000;
+fn load_network(path: &Path) -> Result<NeuralNet, String> {
+    match path.extension().is_some_and(| extension | extension == "bin") {
+        true => NeuralNet::load_binary(path),
+        false => NeuralNet::load_text(path)
+    }
+}
 
-nn.randomize(-1.0, 1.0);
+fn save_network(nn: &NeuralNet, path: &Path) -> Result<(), String> {
+    match path.extension().is_some_and(| extension | extension == "bin") {
+        true => nn.save_binary(path),
+        false => nn.save_text(path)
+    }
+}
 
-let mut input: Vec<Vec<f64>> = vec![    vec![0.0, 0.0, 0.0, 0.0],
-                                        vec![0.0, 1.0, 0.0, 0.0],
-                                        vec![1.0, 0.0, 0.0, 0.0],
-                                        vec![1.0, 1.0, 0.0, 0.0]  ];
+fn check_network_fits(nn: &NeuralNet, trainingset: &Trainingset) -> Result<(), String> {
+    if (nn.get_input_size(), nn.get_output_size()) != (trainingset.get_input_size(), trainingset.get_output_size()) {
+        return Err(format!("The network has {} inputs and {} outputs, the dataset {} inputs and {} targets",
+                           nn.get_input_size(), nn.get_output_size(), trainingset.get_input_size(), trainingset.get_output_size()));
+    }
+    Ok(())
+}
 
-let mut target: Vec<Vec<f64>> = vec![   vec![0.0, 0.0, 0.0, 0.0],
-                                        vec![1.0, 0.0, 0.0, 0.0],
-                                        vec![1.0, 0.0, 0.0, 0.0],
-                                        vec![0.0, 0.0, 0.0, 0.0]  ];
+//a new network with randomized weights, sized to fit the trainingset
+fn create_network(args: &Arguments, trainingset: &Trainingset) -> Result<NeuralNet, String> {
+    let hidden = args.get_with("hidden", | list | parse_list(list, | units | match units.parse::<usize>() {
+        Ok(units) if units > 0 => Ok(units),
+        _ => Err(format!("Invalid unit count \"{}\"", units))
+    }))?.unwrap_or_else(|| vec![8]);
 
-loop {
-    let i = match input.pop() {
-        Some(e) => e,
-        None => break
-    };
+    let mut topology = vec![trainingset.get_input_size()];
+    topology.extend(hidden);
+    topology.push(trainingset.get_output_size());
 
-    let t = match target.pop() {
-        Some(e) => e,
-        None => break
-    };
+    let seed = args.get("seed")?.unwrap_or_else(generate_seed);
+    println!("seed: {}", seed);
 
-    trainingset.add_set(i, t);
+    let mut nn = NeuralNet::new(&topology);
+    nn.randomize(-1.0, 1.0, &mut create_rng(seed));
+    Ok(nn)
+}
+
+//a saved network, its structure and weights are kept
+fn load_trained_network(args: &Arguments, path: &str) -> Result<NeuralNet, String> {
+    for option in &["hidden", "seed"] {
+        if args.has_flag(option) {
+            return Err(format!("--{} can't be combined with --load, the loaded network keeps its structure and weights", option));
+        }
+    }
+    load_network(Path::new(path))
 }
 
-for i in 0..cycles {
-    let avg_error = nn.train(&trainingset, learning_rate, runs_per_cycle);
+//the activations and the loss given as options
+fn configure_network(args: &Arguments, nn: &mut NeuralNet) -> Result<(), String> {
+    if let Some(activation) = args.get_with("hidden-activation", Activation::from_name)? {
+        nn.set_hidden_activation(activation);
+    }
+    if let Some(activation) = args.get_with("output-activation", Activation::from_name)? {
+        nn.set_output_activation(activation);
+    }
+    if let Some(loss) = args.get_with("loss", Loss::from_name)? {
+        nn.set_loss(loss);
+    }
+    Ok(())
+}
 
-    println!("runs: {:06} | learning_rate: {:.2} | avg_error: {:.2e}", i * runs_per_cycle, learning_rate, avg_error);
+fn run_train(args: &Arguments) -> Result<(), String> {
+    let trainingset = Trainingset::load(Path::new(&args.get_positional()[0]))?;
+    let runs = args.get_or("runs", TRAINING_RUNS)?;
+    let report_interval = args.get_or("report", TRAINING_REPORT_INTERVAL)?.max(1);
+    let target_error = args.get_or("target-error", TARGET_ERROR)?;
+    let learning_rate = args.get_or("learning-rate", LEARNING_RATE)?;
+    let batch_size = args.get_or("batch-size", 1)?;
+    if batch_size == 0 {
+        return Err(String::from("The batch size must be positive"));
+    }
 
-    learning_rate *= 0.98;
+    let mut nn = match args.get_str("load") {
+        Some(path) => load_trained_network(args, path)?,
+        None => create_network(args, &trainingset)?
+    };
+    check_network_fits(&nn, &trainingset)?;
+    configure_network(args, &mut nn)?;
+    let optimizer = args.get_str("optimizer").unwrap_or("adam");
+    nn.set_optimizer(optimizer::from_name(optimizer).map_err(| e | format!("Invalid value for --optimizer: {}", e))?);
 
-    if avg_error < 1e-6 {
-        break;
+    println!("topology: {:?} | sets: {}", nn.get_topology(), trainingset.get_set_count());
+
+    let mut runs_done = 0;
+    while runs_done < runs {
+        let cycle_runs = report_interval.min(runs - runs_done);
+        let avg_error = nn.train_batched(&trainingset, learning_rate, cycle_runs, batch_size);
+        runs_done += cycle_runs;
+        //the first call has shown every set to the input normalization
+        nn.set_normalization_frozen(true);
+
+        println!("runs: {:06} | learning_rate: {} | avg_error: {:.2e}", runs_done, learning_rate, avg_error);
+
+        if avg_error < target_error {
+            break;
+        }
     }
+
+    if let Some(path) = args.get_str("output") {
+        save_network(&nn, Path::new(path))?;
+        println!("saved {}", path);
+    }
+    Ok(())
 }
 
-for e in trainingset.iter() {
-    let output = nn.feed_forward(&e.0);
-    println!("XOR: {:?} -> {:?}", &e.0[0..2], &output[0]);
-}*/
+fn run_eval(args: &Arguments) -> Result<(), String> {
+    let mut nn = load_network(Path::new(&args.get_positional()[0]))?;
+    let trainingset = Trainingset::load(Path::new(&args.get_positional()[1]))?;
+    check_network_fits(&nn, &trainingset)?;
+    nn.set_normalization_frozen(true);
+
+    if args.has_flag("verbose") {
+        for set in trainingset.iter() {
+            let output = nn.feed_forward(&set.0);
+            println!("{:?} -> {:?} (target {:?})", set.0, output, set.1);
+        }
+    }
+
+    println!("sets: {} | loss: {} | avg_error: {:.4e}", trainingset.get_set_count(), nn.get_loss().get_name(), nn.evaluate(&trainingset));
+    Ok(())
+}
+
+fn run_bench(args: &Arguments) -> Result<(), String> {
+    let ticks = args.get_or("ticks", BENCHMARK_TICKS)?;
+    let seed = args.get_or("seed", 0)?;
+    let bot_counts = args.get_with("bots", | list | parse_list(list, | count | count.parse::<u32>().map_err(| _ | format!("\"{}\"", count))))?
+        .unwrap_or_else(|| BENCHMARK_BOT_COUNTS.to_vec());
+    print_benchmark(&bot_counts, ticks, seed)
+}
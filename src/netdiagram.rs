@@ -0,0 +1,187 @@
+//A node-link diagram of a neural network: the layers are columns from left to right, the units circles
+//coloured by their output of the last feed_forward and the weights lines coloured by their sign and
+//thickened by their magnitude. The layout is independent of the drawing, the diagram is drawn by any renderer.
+
+use std::io::Write;
+use std::path::Path;
+
+use neuralnet::NeuralNet;
+use color::Color;
+use renderer::Renderer;
+use svg::SvgRenderer;
+
+const MAX_UNIT_RADIUS: f32 = 12.0;
+const MIN_EDGE_THICKNESS: f32 = 0.5;
+const MAX_EDGE_THICKNESS: f32 = 4.0;
+
+fn get_neutral_color() -> Color {
+    Color::from_rgb(0x60, 0x60, 0x60)
+}
+
+fn get_positive_color() -> Color {
+    Color::from_rgb(0x40, 0xE0, 0x40)
+}
+
+fn get_negative_color() -> Color {
+    Color::from_rgb(0xE0, 0x40, 0x40)
+}
+
+//neutral for 0, fully coloured from a magnitude of 1
+fn get_value_color(value: f64) -> Color {
+    match value >= 0.0 {
+        true => get_neutral_color().mix(get_positive_color(), value as f32),
+        false => get_neutral_color().mix(get_negative_color(), -value as f32)
+    }
+}
+
+pub struct DiagramUnit {
+    pub pos: (f32, f32),
+    pub radius: f32,
+    pub value: f64,
+    pub color: Color
+}
+
+pub struct DiagramEdge {
+    pub from: (f32, f32),
+    pub to: (f32, f32),
+    pub weight: f64,
+    pub thickness: f32,
+    pub color: Color
+}
+
+pub struct NetDiagram {
+    size: (f32, f32),
+    units: Vec<DiagramUnit>,
+    edges: Vec<DiagramEdge>
+}
+
+impl NetDiagram {
+
+    //lays the network out in a rectangle of size, with the origin in its upper left corner
+    pub fn new(nn: &NeuralNet, size: (f32, f32)) -> NetDiagram {
+        let topology = nn.get_topology();
+        let column_width = size.0 / topology.len() as f32;
+        let max_units = topology.iter().cloned().max().unwrap_or(1) as f32;
+        let radius = (size.1 / max_units / 2.0 * 0.7).min(column_width * 0.2).min(MAX_UNIT_RADIUS);
+
+        let positions: Vec<Vec<(f32, f32)>> = topology.iter().enumerate()
+            .map(| (layer, &units) | {
+                let row_height = size.1 / units as f32;
+                (0..units).map(| unit | (column_width * (layer as f32 + 0.5), row_height * (unit as f32 + 0.5))).collect()
+            })
+            .collect();
+
+        let mut units = Vec::new();
+        for (layer, layer_positions) in positions.iter().enumerate() {
+            for (unit, &pos) in layer_positions.iter().enumerate() {
+                let value = nn.get_layer_output(layer)[unit];
+                units.push(DiagramUnit {
+                    pos,
+                    radius,
+                    value,
+                    color: get_value_color(value)
+                });
+            }
+        }
+
+        //weights are scaled relative to the largest one, so the diagram shows the structure of any network
+        let max_weight = (0..topology.len() - 1)
+            .flat_map(| layer | nn.get_weights(layer).iter().cloned())
+            .fold(0.0, | max: f64, weight | max.max(weight.abs()));
+
+        let mut edges = Vec::new();
+        for layer in 0..topology.len() - 1 {
+            for (src, &from) in positions[layer].iter().enumerate() {
+                for (dest, &to) in positions[layer + 1].iter().enumerate() {
+                    let weight = nn.get_weight(layer, src, dest);
+                    let magnitude = match max_weight > 0.0 {
+                        true => weight.abs() / max_weight,
+                        false => 0.0
+                    };
+                    edges.push(DiagramEdge {
+                        from,
+                        to,
+                        weight,
+                        thickness: MIN_EDGE_THICKNESS + (MAX_EDGE_THICKNESS - MIN_EDGE_THICKNESS) * magnitude as f32,
+                        color: get_value_color(weight.signum() * magnitude)
+                    });
+                }
+            }
+        }
+
+        NetDiagram {
+            size,
+            units,
+            edges
+        }
+    }
+
+    pub fn get_size(&self) -> (f32, f32) {
+        self.size
+    }
+
+    pub fn get_units(&self) -> &[DiagramUnit] {
+        &self.units
+    }
+
+    pub fn get_edges(&self) -> &[DiagramEdge] {
+        &self.edges
+    }
+
+    //draws the diagram with its upper left corner at pos
+    pub fn draw(&self, renderer: &mut dyn Renderer, pos: (f32, f32)) {
+        let offset = | point: (f32, f32) | (pos.0 + point.0, pos.1 + point.1);
+
+        renderer.draw_filled_rectangle(pos, offset(self.size), Color::from_rgb(0x16, 0x16, 0x16));
+        for edge in self.edges.iter() {
+            renderer.set_tooltip(&format!("weight {}", edge.weight));
+            renderer.draw_line(offset(edge.from), offset(edge.to), edge.color, edge.thickness);
+        }
+        for unit in self.units.iter() {
+            renderer.set_tooltip(&format!("value {}", unit.value));
+            renderer.draw_filled_circle(offset(unit.pos), unit.radius, unit.color);
+            renderer.draw_circle(offset(unit.pos), unit.radius, Color::from_rgb(0xFF, 0xFF, 0xFF), 1.0);
+        }
+    }
+
+    fn render_svg(&self) -> SvgRenderer {
+        let mut renderer = SvgRenderer::new(self.size);
+        self.draw(&mut renderer, (0.0, 0.0));
+        renderer
+    }
+
+    pub fn save_svg(&self, path: &Path) -> Result<(), String> {
+        self.render_svg().save(path)
+    }
+
+    pub fn write_svg<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        self.render_svg().write(writer)
+    }
+}
+
+#[test]
+fn test_net_diagram() {
+    use random::create_rng;
+
+    let mut nn = NeuralNet::new(&[3, 4, 2]);
+    nn.randomize(-1.0, 1.0, &mut create_rng(4));
+    nn.feed_forward(&[0.5, -0.5, 1.0]);
+
+    let diagram = NetDiagram::new(&nn, (300.0, 200.0));
+    assert!(diagram.get_units().len() == 9);
+    assert!(diagram.get_edges().len() == 3 * 4 + 4 * 2);
+    assert!(diagram.get_units().iter().all(| unit | unit.pos.0 > 0.0 && unit.pos.0 < 300.0 && unit.pos.1 > 0.0 && unit.pos.1 < 200.0));
+    //the strongest weight gets the thickest line
+    assert!(diagram.get_edges().iter().any(| edge | edge.thickness == MAX_EDGE_THICKNESS));
+
+    let mut svg = Vec::new();
+    diagram.write_svg(&mut svg).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+    //every unit is a filled circle with an outline
+    assert!(svg.matches("<circle").count() == 2 * 9 && svg.matches("<line").count() == 20);
+    //every edge and unit shows its weight or value when hovered
+    assert!(svg.matches("<title>").count() == 20 + 9);
+    let weight = diagram.get_edges()[0].weight;
+    assert!(svg.contains(&format!("stroke-width=\"{}\"><title>weight {}</title></line>", diagram.get_edges()[0].thickness, weight)));
+}
@@ -1,203 +1,522 @@
+use rand::Rng;
 use rand::distributions::{ Range, IndependentSample };
-use rand;
-use std::f64;
 
-use vectorn::{ Vector2, Vector3 };
+use vectorn::Vector2;
 use trainingset::Trainingset;
+use activation::Activation;
+use loss::Loss;
+use optimizer::{ Optimizer, Sgd };
+use normalization::{ Normalization, Normalizer };
 
 pub struct NeuralNet {
 
-    layers: usize,
-    units: usize,
-    weight: Vector3<f64>,
-    bias: Vector2<f64>,
-    unit_input: Vector2<f64>,
-    unit_output: Vector2<f64>
+    topology: Vec<usize>,
+    activation: Vec<Activation>,
+    loss: Loss,
+    optimizer: Box<dyn Optimizer>,
+    normalizer: Vec<Normalizer>,
+    normalization_frozen: bool,
+    weight: Vec<Vector2<f64>>,
+    bias: Vec<Vec<f64>>,
+    unit_input: Vec<Vec<f64>>,
+    unit_output: Vec<Vec<f64>>,
+    weight_gradient: Vec<Vector2<f64>>,
+    bias_gradient: Vec<Vec<f64>>,
+    gradient_count: usize
 }
 
-fn activation(input: f64) -> f64 {
-    1.0 / (1.0 + f64::consts::E.powf(-input))
-}
+impl NeuralNet {
 
-fn activation_derivative(input: f64) -> f64 {
-    let act = activation(input);
-    act * (1f64 - act)
-}
+    //topology contains the unit count of every layer, starting with the input layer
+    //all layers after the input layer start with a sigmoid activation, trained on the squared error by plain sgd
+    pub fn new(topology: &[usize]) -> NeuralNet {
+        assert!(topology.len() >= 2);
+        assert!(topology.iter().all(| &units | units > 0));
 
-fn square_error(current: &[f64], target: &[f64]) -> f64 {
-    let mut square_sum = 0f64;
-    for i in 0..current.len() {
-        square_sum += (target[i] - current[i]).powf(2f64);
-    }
-    0.5 * square_sum
-}
+        let mut activation = vec![Activation::Sigmoid; topology.len()];
+        activation[0] = Activation::Linear;
 
-impl NeuralNet {
-    pub fn new(layers: usize, units: usize) -> NeuralNet {
         NeuralNet {
-            layers: layers,
-            units: units,
-            weight: Vector3::new(0.0, layers, units, units),
-            bias: Vector2::new(0.0, layers, units),
-            unit_input: Vector2::new(0.0, layers, units),
-            unit_output: Vector2::new(0.0, layers, units)
+            topology: topology.to_vec(),
+            activation,
+            loss: Loss::SquaredError,
+            optimizer: Box::new(Sgd),
+            normalizer: vec![Normalizer::new(Normalization::None); topology[0]],
+            normalization_frozen: false,
+            weight: topology.windows(2).map(| pair | Vector2::new(0.0, pair[0], pair[1])).collect(),
+            bias: topology.iter().map(| &units | vec![0.0; units]).collect(),
+            unit_input: topology.iter().map(| &units | vec![0.0; units]).collect(),
+            unit_output: topology.iter().map(| &units | vec![0.0; units]).collect(),
+            weight_gradient: topology.windows(2).map(| pair | Vector2::new(0.0, pair[0], pair[1])).collect(),
+            bias_gradient: topology.iter().map(| &units | vec![0.0; units]).collect(),
+            gradient_count: 0
         }
     }
 
-    fn get_unit_input(&self, layer: usize, unit: usize) -> f64{
-        *self.unit_input.get(layer, unit)
+    pub fn get_topology(&self) -> &[usize] {
+        &self.topology
+    }
+
+    pub fn get_layer_count(&self) -> usize {
+        self.topology.len()
+    }
+
+    pub fn get_unit_count(&self, layer: usize) -> usize {
+        self.topology[layer]
+    }
+
+    pub fn get_input_size(&self) -> usize {
+        self.topology[0]
+    }
+
+    pub fn get_output_size(&self) -> usize {
+        self.topology[self.topology.len() - 1]
+    }
+
+    //a network with the same topology, activations, loss and input normalization, but zeroed parameters
+    pub fn clone_structure(&self) -> NeuralNet {
+        let mut nn = NeuralNet::new(&self.topology);
+        nn.activation = self.activation.clone();
+        nn.loss = self.loss;
+        nn.normalizer = self.normalizer.clone();
+        nn
+    }
+
+    //all weights followed by the bias of all layers after the input layer
+    pub fn get_parameters(&self) -> Vec<f64> {
+        let mut parameters = Vec::with_capacity(self.get_parameter_count());
+        for layer_weight in self.weight.iter() {
+            parameters.extend_from_slice(layer_weight.as_slice());
+        }
+        for layer_bias in self.bias.iter().skip(1) {
+            parameters.extend_from_slice(layer_bias);
+        }
+        parameters
+    }
+
+    pub fn set_parameters(&mut self, parameters: &[f64]) {
+        assert_eq!(parameters.len(), self.get_parameter_count());
+
+        let mut offset = 0;
+        for layer_weight in self.weight.iter_mut() {
+            let len = layer_weight.as_slice().len();
+            layer_weight.as_mut_slice().copy_from_slice(&parameters[offset..offset + len]);
+            offset += len;
+        }
+        for layer_bias in self.bias.iter_mut().skip(1) {
+            let len = layer_bias.len();
+            layer_bias.copy_from_slice(&parameters[offset..offset + len]);
+            offset += len;
+        }
+    }
+
+    pub fn get_parameter_count(&self) -> usize {
+        self.topology.windows(2).fold(0, | sum, pair | sum + pair[0] * pair[1] + pair[1])
+    }
+
+    pub fn get_activation(&self, layer: usize) -> Activation {
+        self.activation[layer]
+    }
+
+    pub fn set_activation(&mut self, layer: usize, activation: Activation) {
+        assert!(layer > 0 && layer < self.topology.len());
+        self.activation[layer] = activation;
+    }
+
+    pub fn set_hidden_activation(&mut self, activation: Activation) {
+        for layer in 1..self.topology.len() - 1 {
+            self.activation[layer] = activation;
+        }
+    }
+
+    pub fn set_output_activation(&mut self, activation: Activation) {
+        let last_layer = self.topology.len() - 1;
+        self.activation[last_layer] = activation;
+    }
+
+    pub fn get_loss(&self) -> Loss {
+        self.loss
+    }
+
+    pub fn set_loss(&mut self, loss: Loss) {
+        self.loss = loss;
+    }
+
+    //one normalization per input, this resets the running statistics
+    pub fn set_input_normalization(&mut self, normalization: &[Normalization]) {
+        assert_eq!(normalization.len(), self.get_input_size());
+        self.normalizer = normalization.iter().map(| &e | Normalizer::new(e)).collect();
+    }
+
+    pub fn get_normalizer(&self, input: usize) -> &Normalizer {
+        &self.normalizer[input]
+    }
+
+    pub fn set_normalizer(&mut self, input: usize, normalizer: Normalizer) {
+        self.normalizer[input] = normalizer;
+    }
+
+    //frozen normalizers keep their statistics, e.g. to evaluate a trained network
+    pub fn set_normalization_frozen(&mut self, frozen: bool) {
+        self.normalization_frozen = frozen;
+    }
+
+    pub fn is_normalization_frozen(&self) -> bool {
+        self.normalization_frozen
+    }
+
+    pub fn set_optimizer(&mut self, optimizer: Box<dyn Optimizer>) {
+        self.optimizer = optimizer;
+    }
+
+    pub fn get_optimizer(&self) -> &dyn Optimizer {
+        &*self.optimizer
+    }
+
+    pub fn get_optimizer_mut(&mut self) -> &mut dyn Optimizer {
+        &mut *self.optimizer
+    }
+
+    //the weights from layer to layer + 1, indexed by source and destination unit
+    pub fn get_weights(&self, layer: usize) -> &Vector2<f64> {
+        &self.weight[layer]
+    }
+
+    //the unit outputs of the layer in the last feed_forward, for layer 0 the normalized input
+    pub fn get_layer_output(&self, layer: usize) -> &[f64] {
+        &self.unit_output[layer]
     }
 
     fn set_unit_input(&mut self, value: f64, layer: usize, unit: usize) {
-        *self.unit_input.get_mut(layer, unit) = value
+        self.unit_input[layer][unit] = value
     }
 
     fn mod_unit_input(&mut self, value: f64, layer: usize, unit: usize) {
-        *self.unit_input.get_mut(layer, unit) += value
+        self.unit_input[layer][unit] += value
     }
 
     fn get_unit_output(&self, layer: usize, unit: usize) -> f64{
-        *self.unit_output.get(layer, unit)
+        self.unit_output[layer][unit]
     }
 
     fn set_unit_output(&mut self, value: f64, layer: usize, unit: usize) {
-        *self.unit_output.get_mut(layer, unit) = value
+        self.unit_output[layer][unit] = value
     }
 
-    fn get_bias(&self, layer: usize, unit: usize) -> f64 {
-        *self.bias.get(layer, unit)
+    pub fn get_bias(&self, layer: usize, unit: usize) -> f64 {
+        self.bias[layer][unit]
     }
 
-    fn mod_bias(&mut self, value: f64, layer: usize, unit: usize) {
-        *self.bias.get_mut(layer, unit) += value;
+    pub fn set_bias(&mut self, value: f64, layer: usize, unit: usize) {
+        assert!(layer > 0);
+        self.bias[layer][unit] = value;
     }
 
-    fn activate_unit(&mut self, layer: usize, unit: usize) {
-        let bias = self.get_bias(layer, unit);
-        self.mod_unit_input(bias, layer, unit);
-        let output = activation(self.get_unit_input(layer, unit));
-        self.set_unit_output(output, layer, unit)
+    fn activate_layer(&mut self, layer: usize) {
+        for unit in 0..self.topology[layer] {
+            let bias = self.get_bias(layer, unit);
+            self.mod_unit_input(bias, layer, unit);
+        }
+        self.activation[layer].activate(&self.unit_input[layer], &mut self.unit_output[layer]);
     }
 
-    fn get_weight(&self, layer: usize, unit_src: usize, unit_dest: usize) -> f64{
-        *self.weight.get(layer, unit_src, unit_dest)
+    //weights of layer connect the units of layer with the units of layer + 1
+    pub fn get_weight(&self, layer: usize, unit_src: usize, unit_dest: usize) -> f64{
+        *self.weight[layer].get(unit_src, unit_dest)
     }
 
-    fn mod_weight(&mut self, value: f64, layer: usize, unit_src: usize, unit_dest: usize) {
-        *self.weight.get_mut(layer, unit_src, unit_dest) += value
+    pub fn set_weight(&mut self, value: f64, layer: usize, unit_src: usize, unit_dest: usize) {
+        *self.weight[layer].get_mut(unit_src, unit_dest) = value
     }
 
     fn clear_units(&mut self) {
-        self.unit_input.clear(0.0);
-        self.unit_output.clear(0.0);
+        for layer in 0..self.topology.len() {
+            for unit in 0..self.topology[layer] {
+                self.unit_input[layer][unit] = 0.0;
+                self.unit_output[layer][unit] = 0.0;
+            }
+        }
     }
 
-    pub fn randomize(&mut self, lower: f64, upper: f64) {
-        self.randomize_weights(lower, upper);
-        self.randomize_bias(lower, upper);
+    pub fn randomize<R: Rng>(&mut self, lower: f64, upper: f64, rng: &mut R) {
+        self.randomize_weights(lower, upper, rng);
+        self.randomize_bias(lower, upper, rng);
     }
 
-    pub fn randomize_weights(&mut self, lower: f64, upper: f64) {
+    pub fn randomize_weights<R: Rng>(&mut self, lower: f64, upper: f64, rng: &mut R) {
         let range = Range::new(lower, upper);
-        let mut rng = rand::thread_rng();
 
-        for e in self.weight.iter_mut() {
-            *e = range.ind_sample(&mut rng);
+        for layer_weight in self.weight.iter_mut() {
+            for e in layer_weight.iter_mut() {
+                *e = range.ind_sample(rng);
+            }
         }
     }
 
-    pub fn randomize_bias(&mut self, lower: f64, upper: f64) {
+    //the input layer has no bias, so it stays at zero
+    pub fn randomize_bias<R: Rng>(&mut self, lower: f64, upper: f64, rng: &mut R) {
         let range = Range::new(lower, upper);
-        let mut rng = rand::thread_rng();
 
-        for e in &mut self.bias.iter_mut() {
-            *e = range.ind_sample(&mut rng);
+        for layer_bias in self.bias.iter_mut().skip(1) {
+            for e in layer_bias.iter_mut() {
+                *e = range.ind_sample(rng);
+            }
         }
     }
 
-    pub fn feed_forward(&mut self, net_input: &Vec<f64>) -> Vec<f64> {
+    pub fn feed_forward(&mut self, net_input: &[f64]) -> Vec<f64> {
+        assert_eq!(net_input.len(), self.get_input_size());
+
         self.clear_units();
 
-        for unit in 0..self.units {
-            self.set_unit_input(net_input[unit], 0, unit);
-            self.set_unit_output(net_input[unit], 0, unit);
+        for (unit, &input) in net_input.iter().enumerate() {
+            if !self.normalization_frozen {
+                self.normalizer[unit].update(input);
+            }
+            let value = self.normalizer[unit].normalize(input);
+            self.set_unit_input(value, 0, unit);
+            self.set_unit_output(value, 0, unit);
         }
 
-        for layer in 0..self.layers - 1 {
-            for unit in 0..self.units {
-                if layer > 0 {
-                    self.activate_unit(layer, unit);
-                }
-                for dest_unit in 0..self.units {
+        for layer in 0..self.topology.len() - 1 {
+            if layer > 0 {
+                self.activate_layer(layer);
+            }
+            for unit in 0..self.topology[layer] {
+                for dest_unit in 0..self.topology[layer + 1] {
                     let input = self.get_unit_output(layer, unit) * self.get_weight(layer, unit, dest_unit);
                     self.mod_unit_input(input, layer + 1, dest_unit)
                 }
             }
         }
 
-        let last_layer = self.layers - 1;
-        let mut output = vec![0f64; self.units];
-        for unit in 0..self.units {
-            self.activate_unit(last_layer, unit);
-            output[unit] = self.get_unit_output(last_layer, unit);
-        }
-        output
+        let last_layer = self.topology.len() - 1;
+        self.activate_layer(last_layer);
+        self.unit_output[last_layer].clone()
     }
 
-    pub fn backpropagate(&mut self, target: &Vec<f64>, learning_rate: f64) -> f64 {
+    //backpropagation with an immediate update, i.e. a batch size of 1
+    pub fn backpropagate(&mut self, target: &[f64], learning_rate: f64) -> f64 {
+        let error = self.accumulate_gradients(target);
+        self.apply_gradients(learning_rate);
+        error
+    }
 
-        let mut delta = vec![0f64; self.layers * self.units];
-        let last_layer = self.layers - 1;
+    //adds the gradients for target of the last feed_forward to the pending gradients
+    pub fn accumulate_gradients(&mut self, target: &[f64]) -> f64 {
+        assert_eq!(target.len(), self.get_output_size());
 
-        for unit in 0..self.units {
-            delta[last_layer * self.units + unit] = activation_derivative(self.get_unit_input(last_layer, unit)) * (target[unit] - self.get_unit_output(last_layer, unit));
-        }
+        let mut delta: Vec<Vec<f64>> = self.topology.iter().map(| &units | vec![0f64; units]).collect();
+        let last_layer = self.topology.len() - 1;
 
-        for layer in (0..self.layers - 1).rev() {
-            for unit in 0..self.units {
-                let input = self.get_unit_input(layer, unit);
-                //let output = self.get_unit_output(layer, unit);
+        self.loss.output_delta(self.activation[last_layer], &self.unit_input[last_layer], &self.unit_output[last_layer], target, &mut delta[last_layer]);
 
+        //the input layer needs no delta, it has neither incoming weights nor a bias
+        for layer in (1..last_layer).rev() {
+            for unit in 0..self.topology[layer] {
                 let mut delta_sum = 0f64;
-                {
-                    let prev_delta = &delta[(layer + 1) * self.units..(layer + 2) * self.units];
-                    for unit_dest in 0..self.units {
-                        delta_sum += prev_delta[unit_dest] * self.get_weight(layer, unit, unit_dest);
-                    }
+                for (unit_dest, &delta_dest) in delta[layer + 1].iter().enumerate() {
+                    delta_sum += delta_dest * self.get_weight(layer, unit, unit_dest);
                 }
-
-                delta[layer * self.units + unit] = activation_derivative(input) * delta_sum;
+                delta[layer][unit] = delta_sum;
             }
+            self.activation[layer].backpropagate(&self.unit_input[layer], &self.unit_output[layer], &mut delta[layer]);
         }
 
-        for layer in 0..self.layers - 1 {
-            for unit in 0..self.units {
-                for unit_dest in 0..self.units {
-                    let output = self.get_unit_output(layer, unit);
-                    let change = learning_rate * delta[(layer + 1) * self.units + unit_dest] * output;
-                    self.mod_weight(change, layer, unit, unit_dest);
+        for layer in 0..last_layer {
+            for unit in 0..self.topology[layer] {
+                let output = self.get_unit_output(layer, unit);
+                for (unit_dest, &delta_dest) in delta[layer + 1].iter().enumerate() {
+                    *self.weight_gradient[layer].get_mut(unit, unit_dest) += delta_dest * output;
                 }
+            }
+            for (gradient, &delta_unit) in self.bias_gradient[layer + 1].iter_mut().zip(&delta[layer + 1]) {
+                *gradient += delta_unit;
+            }
+        }
+        self.gradient_count += 1;
 
-                //TODO Check if correct. It seems it has no negative impact.
-                let bias = self.get_bias(layer, unit);
-                let change = learning_rate * delta[(layer + 1) * self.units + unit] * bias;
-                self.mod_bias(change, layer, unit);
+        self.loss.error(&self.unit_output[last_layer], target)
+    }
+
+    //applies the average of the pending gradients and clears them
+    pub fn apply_gradients(&mut self, learning_rate: f64) {
+        if self.gradient_count == 0 {
+            return;
+        }
+
+        let scale = 1.0 / self.gradient_count as f64;
+        for layer_gradient in self.weight_gradient.iter_mut() {
+            for e in layer_gradient.iter_mut() {
+                *e *= scale;
+            }
+        }
+        for layer_gradient in self.bias_gradient.iter_mut() {
+            for e in layer_gradient.iter_mut() {
+                *e *= scale;
             }
         }
 
-        square_error(self.unit_output.get_row(last_layer), target)
+        //the weights of layer are updated as group 2 * layer, the bias of layer + 1 as group 2 * layer + 1
+        self.optimizer.begin_update();
+        for layer in 0..self.topology.len() - 1 {
+            self.optimizer.update(2 * layer, self.weight[layer].as_mut_slice(), self.weight_gradient[layer].as_slice(), learning_rate);
+            self.optimizer.update(2 * layer + 1, &mut self.bias[layer + 1], &self.bias_gradient[layer + 1], learning_rate);
+        }
+
+        self.clear_gradients();
+    }
+
+    pub fn clear_gradients(&mut self) {
+        for layer_gradient in self.weight_gradient.iter_mut() {
+            layer_gradient.clear(0.0);
+        }
+        for layer_gradient in self.bias_gradient.iter_mut() {
+            for e in layer_gradient.iter_mut() {
+                *e = 0.0;
+            }
+        }
+        self.gradient_count = 0;
     }
 
     pub fn train(&mut self, trainingset: &Trainingset, learning_rate: f64, runs: u32) -> f64 {
+        self.train_batched(trainingset, learning_rate, runs, 1)
+    }
+
+    //applies one averaged update per batch_size sets,
+    //a batch_size of at least the set count trains on the full batch
+    pub fn train_batched(&mut self, trainingset: &Trainingset, learning_rate: f64, runs: u32, batch_size: usize) -> f64 {
+        assert!(batch_size > 0);
+
+        let frozen = self.normalization_frozen;
         let mut avg_error = 0f64;
-        for _ in 0..runs {
+        for run in 0..runs {
+            //the input normalization observes every set once, the following runs revisit them
+            self.normalization_frozen = frozen || run > 0;
             let mut total_error = 0f64;
-            for ts in trainingset.iter() {
+            for (index, ts) in trainingset.iter().enumerate() {
                 self.feed_forward(&ts.0);
-                total_error += self.backpropagate(&ts.1, learning_rate);
+                total_error += self.accumulate_gradients(&ts.1);
+                if (index + 1) % batch_size == 0 {
+                    self.apply_gradients(learning_rate);
+                }
             }
+            self.apply_gradients(learning_rate);
             avg_error = total_error / trainingset.get_set_count() as f64;
         }
+        self.normalization_frozen = frozen;
         avg_error
     }
+
+    //the average error over all sets without training, the input normalization stays unchanged
+    pub fn evaluate(&mut self, trainingset: &Trainingset) -> f64 {
+        let frozen = self.normalization_frozen;
+        self.normalization_frozen = true;
+
+        let mut total_error = 0f64;
+        for ts in trainingset.iter() {
+            let output = self.feed_forward(&ts.0);
+            total_error += self.loss.error(&output, &ts.1);
+        }
+
+        self.normalization_frozen = frozen;
+        total_error / trainingset.get_set_count() as f64
+    }
+}
+
+#[cfg(test)]
+use random::create_rng;
+
+#[test]
+fn test_uneven_topology() {
+    let mut nn = NeuralNet::new(&[6, 12, 8, 3]);
+    nn.randomize(-1.0, 1.0, &mut create_rng(1));
+
+    let output = nn.feed_forward(&[0.5; 6]);
+    assert!(output.len() == 3);
+
+    let error = nn.backpropagate(&[1.0, 0.0, 0.5], 0.5);
+    assert!(error.is_finite());
+}
+
+#[test]
+fn test_training_observes_inputs_once() {
+    use normalization::Normalization;
+
+    let mut trainingset = Trainingset::new();
+    for i in 0..4 {
+        trainingset.add_set(vec![i as f64, 1.0], vec![0.5]);
+    }
+
+    let mut nn = NeuralNet::new(&[2, 3, 1]);
+    nn.set_input_normalization(&[Normalization::Standardize; 2]);
+    nn.train_batched(&trainingset, 0.1, 5, 2);
+    assert!(nn.get_normalizer(0).get_count() == 4 && nn.get_normalizer(0).get_mean() == 1.5);
+    assert!(!nn.is_normalization_frozen());
+
+    nn.evaluate(&trainingset);
+    assert!(nn.get_normalizer(1).get_count() == 4);
+}
+
+#[test]
+fn test_batch_gradient_is_mean() {
+    let samples = [([0.5, -1.0], [1.0]), ([0.2, 0.3], [0.0]), ([-0.8, 0.6], [0.5])];
+    let mut nn = NeuralNet::new(&[2, 3, 1]);
+    nn.randomize(-1.0, 1.0, &mut create_rng(2));
+    let start = nn.get_parameters();
+
+    //with sgd the change of a step is the learning rate times the gradient
+    let step = | nn: &mut NeuralNet, parameters: &[f64], (input, target): &([f64; 2], [f64; 1]) | {
+        nn.set_parameters(parameters);
+        nn.feed_forward(input);
+        nn.backpropagate(target, 0.1);
+        nn.get_parameters().iter().zip(parameters.iter()).map(| (after, before) | after - before).collect::<Vec<f64>>()
+    };
+    let mut single = nn.clone_structure();
+    let changes: Vec<Vec<f64>> = samples.iter().map(| sample | step(&mut single, &start, sample)).collect();
+
+    for (input, target) in samples.iter() {
+        nn.feed_forward(input);
+        nn.accumulate_gradients(target);
+    }
+    nn.apply_gradients(0.1);
+    let batched = nn.get_parameters();
+    for (i, (after, before)) in batched.iter().zip(start.iter()).enumerate() {
+        let mean = changes.iter().map(| change | change[i]).sum::<f64>() / samples.len() as f64;
+        assert!((after - before - mean).abs() < 1e-12);
+    }
+
+    //the applied gradients are cleared, so the next step only uses the next sample
+    nn.apply_gradients(0.1);
+    assert!(nn.get_parameters() == batched);
+    nn.feed_forward(&samples[0].0);
+    nn.accumulate_gradients(&samples[0].1);
+    nn.apply_gradients(0.1);
+    let change = step(&mut single, &batched, &samples[0]);
+    for ((after, before), expected) in nn.get_parameters().iter().zip(batched.iter()).zip(change.iter()) {
+        assert!((after - before - expected).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_optimizer_groups_of_layers() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    //records the group and the size of every update
+    struct Recorder(Rc<RefCell<Vec<(usize, usize)>>>);
+    impl Optimizer for Recorder {
+        fn update(&mut self, group: usize, parameters: &mut [f64], _gradients: &[f64], _learning_rate: f64) {
+            self.0.borrow_mut().push((group, parameters.len()));
+        }
+    }
+
+    let updates = Rc::new(RefCell::new(Vec::new()));
+    let mut nn = NeuralNet::new(&[3, 4, 2]);
+    nn.set_optimizer(Box::new(Recorder(updates.clone())));
+    nn.feed_forward(&[0.1, 0.2, 0.3]);
+    nn.backpropagate(&[1.0, 0.0], 0.1);
+
+    //the weights of layer are group 2 * layer, the bias of layer + 1 is group 2 * layer + 1
+    assert!(*updates.borrow() == vec![(0, 3 * 4), (1, 4), (2, 4 * 2), (3, 2)]);
 }
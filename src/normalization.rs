@@ -0,0 +1,154 @@
+//keeps the standardization finite for inputs which never change
+const MIN_DEVIATION: f64 = 1e-8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Normalization {
+    None,
+    //maps [min, max] linearly to [-1, 1]
+    Range(f64, f64),
+    //scales to zero mean and unit variance, using the running mean and variance of all inputs seen
+    Standardize
+}
+
+impl Normalization {
+
+    pub fn get_name(&self) -> String {
+        match *self {
+            Normalization::None => String::from("none"),
+            Normalization::Range(min, max) => format!("range:{}:{}", min, max),
+            Normalization::Standardize => String::from("standardize")
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Normalization, String> {
+        match name {
+            "none" => Ok(Normalization::None),
+            "standardize" => Ok(Normalization::Standardize),
+            _ if name.starts_with("range:") => {
+                let bounds: Vec<&str> = name["range:".len()..].split(':').collect();
+                match (bounds.len(), bounds[0].parse::<f64>(), bounds.get(1).map(| e | e.parse::<f64>())) {
+                    (2, Ok(min), Some(Ok(max))) if min.is_finite() && max.is_finite() && min < max => Ok(Normalization::Range(min, max)),
+                    _ => Err(format!("Invalid range in \"{}\"", name))
+                }
+            },
+            _ => Err(format!("Unknown normalization \"{}\"", name))
+        }
+    }
+}
+
+//The normalization of a single network input, together with its running statistics.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Normalizer {
+    normalization: Normalization,
+    count: u64,
+    mean: f64,
+    //sum of the squared deviations from the mean, see Welford's algorithm
+    square_sum: f64
+}
+
+impl Normalizer {
+
+    pub fn new(normalization: Normalization) -> Normalizer {
+        if let Normalization::Range(min, max) = normalization {
+            assert!(min < max);
+        }
+
+        Normalizer {
+            normalization,
+            count: 0,
+            mean: 0.0,
+            square_sum: 0.0
+        }
+    }
+
+    pub fn get_normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    pub fn get_count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn get_mean(&self) -> f64 {
+        self.mean
+    }
+
+    pub fn get_variance(&self) -> f64 {
+        match self.count {
+            0 => 0.0,
+            count => self.square_sum / count as f64
+        }
+    }
+
+    pub fn update(&mut self, value: f64) {
+        if self.normalization != Normalization::Standardize {
+            return;
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.square_sum += delta * (value - self.mean);
+    }
+
+    pub fn normalize(&self, value: f64) -> f64 {
+        match self.normalization {
+            Normalization::None => value,
+            Normalization::Range(min, max) => 2.0 * (value - min) / (max - min) - 1.0,
+            Normalization::Standardize => (value - self.mean) / self.get_variance().sqrt().max(MIN_DEVIATION)
+        }
+    }
+
+    //the normalization with its statistics, e.g. "standardize:10:0.5:2.25"
+    pub fn get_name(&self) -> String {
+        match self.normalization {
+            Normalization::Standardize => format!("standardize:{}:{}:{}", self.count, self.mean, self.square_sum),
+            normalization => normalization.get_name()
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Normalizer, String> {
+        if !name.starts_with("standardize:") {
+            return Ok(Normalizer::new(Normalization::from_name(name)?));
+        }
+
+        let fields: Vec<&str> = name["standardize:".len()..].split(':').collect();
+        if fields.len() != 3 {
+            return Err(format!("Invalid statistics in \"{}\"", name));
+        }
+        //the square sum is a sum of squares, without samples all statistics are 0
+        match (fields[0].parse::<u64>(), fields[1].parse::<f64>(), fields[2].parse::<f64>()) {
+            (Ok(count), Ok(mean), Ok(square_sum))
+                if mean.is_finite() && square_sum.is_finite() && square_sum >= 0.0 && (count > 0 || (mean == 0.0 && square_sum == 0.0)) => Ok(Normalizer {
+                normalization: Normalization::Standardize,
+                count,
+                mean,
+                square_sum
+            }),
+            _ => Err(format!("Invalid statistics in \"{}\"", name))
+        }
+    }
+}
+
+#[test]
+fn test_normalizer() {
+    let range = Normalizer::new(Normalization::Range(0.0, 100.0));
+    assert!(range.normalize(0.0) == -1.0 && range.normalize(50.0) == 0.0 && range.normalize(100.0) == 1.0);
+
+    let mut standardize = Normalizer::new(Normalization::Standardize);
+    for &value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0].iter() {
+        standardize.update(value);
+    }
+    assert!(standardize.get_mean() == 5.0 && standardize.get_variance() == 4.0);
+    assert!(standardize.normalize(9.0) == 2.0);
+
+    let restored = Normalizer::from_name(&standardize.get_name()).unwrap();
+    assert!(restored == standardize);
+    assert!(Normalizer::from_name("range:0:100").unwrap() == range);
+    assert!(Normalization::from_name("range:5:1").is_err());
+
+    //statistics which would break the normalization are rejected when loaded
+    for name in ["range:1:1", "range:NaN:1", "range:-inf:inf", "standardize:-1:0:0", "standardize:4:NaN:1",
+                 "standardize:4:0:-1", "standardize:4:0:inf", "standardize:0:3:0", "standardize:4:0"].iter() {
+        assert!(Normalizer::from_name(name).is_err(), "{}", name);
+    }
+}
@@ -0,0 +1,342 @@
+//An optimizer turns gradients into parameter changes.
+//Parameters are passed in groups (e.g. the weights between two layers),
+//every group keeps its own state, identified by the group index.
+pub trait Optimizer {
+    //called once per update step, before the groups are updated
+    fn begin_update(&mut self) {}
+    fn update(&mut self, group: usize, parameters: &mut [f64], gradients: &[f64], learning_rate: f64);
+    fn reset(&mut self) {}
+    //the internal state as lists of numbers, e.g. to store it with a snapshot
+    fn get_state(&self) -> Vec<Vec<f64>> {
+        Vec::new()
+    }
+    fn set_state(&mut self, _state: Vec<Vec<f64>>) {}
+}
+
+fn get_state(state: &mut Vec<Vec<f64>>, group: usize, size: usize) -> &mut Vec<f64> {
+    while state.len() <= group {
+        state.push(Vec::new());
+    }
+    if state[group].len() != size {
+        state[group] = vec![0.0; size];
+    }
+    &mut state[group]
+}
+
+//an optimizer by name: "sgd", "momentum:<momentum>", "nesterov:<momentum>", "rmsprop:<decay>" or "adam",
+//the parameter is optional and defaults to 0.9, sgd and adam take none
+pub fn from_name(name: &str) -> Result<Box<dyn Optimizer>, String> {
+    let mut parts = name.splitn(2, ':');
+    let kind = parts.next().unwrap_or("");
+    let parameter = match parts.next() {
+        Some(_) if kind == "sgd" || kind == "adam" => return Err(format!("The optimizer {} takes no parameter", kind)),
+        Some(parameter) => match parameter.parse::<f64>() {
+            Ok(value) if (0.0..1.0).contains(&value) => value,
+            _ => return Err(format!("Invalid parameter in \"{}\"", name))
+        },
+        None => 0.9
+    };
+
+    match kind {
+        "sgd" => Ok(Box::new(Sgd)),
+        "momentum" => Ok(Box::new(Momentum::new(parameter))),
+        "nesterov" => Ok(Box::new(Nesterov::new(parameter))),
+        "rmsprop" => Ok(Box::new(RmsProp::new(parameter))),
+        "adam" => Ok(Box::new(Adam::new())),
+        _ => Err(format!("Unknown optimizer \"{}\"", name))
+    }
+}
+
+pub struct Sgd;
+
+impl Optimizer for Sgd {
+    fn update(&mut self, _group: usize, parameters: &mut [f64], gradients: &[f64], learning_rate: f64) {
+        for (param, &grad) in parameters.iter_mut().zip(gradients.iter()) {
+            *param -= learning_rate * grad;
+        }
+    }
+}
+
+pub struct Momentum {
+    momentum: f64,
+    velocity: Vec<Vec<f64>>
+}
+
+impl Momentum {
+    pub fn new(momentum: f64) -> Momentum {
+        Momentum {
+            momentum,
+            velocity: Vec::new()
+        }
+    }
+}
+
+impl Optimizer for Momentum {
+    fn update(&mut self, group: usize, parameters: &mut [f64], gradients: &[f64], learning_rate: f64) {
+        let momentum = self.momentum;
+        let velocity = get_state(&mut self.velocity, group, parameters.len());
+        for i in 0..parameters.len() {
+            velocity[i] = momentum * velocity[i] - learning_rate * gradients[i];
+            parameters[i] += velocity[i];
+        }
+    }
+
+    fn reset(&mut self) {
+        self.velocity.clear();
+    }
+
+    fn get_state(&self) -> Vec<Vec<f64>> {
+        self.velocity.clone()
+    }
+
+    fn set_state(&mut self, state: Vec<Vec<f64>>) {
+        self.velocity = state;
+    }
+}
+
+//Nesterov momentum, in the formulation which only needs the gradient at the current parameters
+pub struct Nesterov {
+    momentum: f64,
+    velocity: Vec<Vec<f64>>
+}
+
+impl Nesterov {
+    pub fn new(momentum: f64) -> Nesterov {
+        Nesterov {
+            momentum,
+            velocity: Vec::new()
+        }
+    }
+}
+
+impl Optimizer for Nesterov {
+    fn update(&mut self, group: usize, parameters: &mut [f64], gradients: &[f64], learning_rate: f64) {
+        let momentum = self.momentum;
+        let velocity = get_state(&mut self.velocity, group, parameters.len());
+        for i in 0..parameters.len() {
+            velocity[i] = momentum * velocity[i] + gradients[i];
+            parameters[i] -= learning_rate * (gradients[i] + momentum * velocity[i]);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.velocity.clear();
+    }
+
+    fn get_state(&self) -> Vec<Vec<f64>> {
+        self.velocity.clone()
+    }
+
+    fn set_state(&mut self, state: Vec<Vec<f64>>) {
+        self.velocity = state;
+    }
+}
+
+pub struct RmsProp {
+    decay: f64,
+    epsilon: f64,
+    square_avg: Vec<Vec<f64>>
+}
+
+impl RmsProp {
+    pub fn new(decay: f64) -> RmsProp {
+        RmsProp {
+            decay,
+            epsilon: 1e-8,
+            square_avg: Vec::new()
+        }
+    }
+}
+
+impl Optimizer for RmsProp {
+    fn update(&mut self, group: usize, parameters: &mut [f64], gradients: &[f64], learning_rate: f64) {
+        let (decay, epsilon) = (self.decay, self.epsilon);
+        let square_avg = get_state(&mut self.square_avg, group, parameters.len());
+        for i in 0..parameters.len() {
+            square_avg[i] = decay * square_avg[i] + (1.0 - decay) * gradients[i] * gradients[i];
+            parameters[i] -= learning_rate * gradients[i] / (square_avg[i].sqrt() + epsilon);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.square_avg.clear();
+    }
+
+    fn get_state(&self) -> Vec<Vec<f64>> {
+        self.square_avg.clone()
+    }
+
+    fn set_state(&mut self, state: Vec<Vec<f64>>) {
+        self.square_avg = state;
+    }
+}
+
+pub struct Adam {
+    beta1: f64,
+    beta2: f64,
+    epsilon: f64,
+    step: i32,
+    first_moment: Vec<Vec<f64>>,
+    second_moment: Vec<Vec<f64>>
+}
+
+impl Adam {
+    pub fn new() -> Adam {
+        Adam::with_betas(0.9, 0.999)
+    }
+
+    pub fn with_betas(beta1: f64, beta2: f64) -> Adam {
+        Adam {
+            beta1,
+            beta2,
+            epsilon: 1e-8,
+            step: 0,
+            first_moment: Vec::new(),
+            second_moment: Vec::new()
+        }
+    }
+}
+
+impl Default for Adam {
+    fn default() -> Adam {
+        Adam::new()
+    }
+}
+
+impl Optimizer for Adam {
+    fn begin_update(&mut self) {
+        self.step += 1;
+    }
+
+    fn update(&mut self, group: usize, parameters: &mut [f64], gradients: &[f64], learning_rate: f64) {
+        let (beta1, beta2, epsilon) = (self.beta1, self.beta2, self.epsilon);
+        let step = self.step.max(1);
+        let correction1 = 1.0 - beta1.powi(step);
+        let correction2 = 1.0 - beta2.powi(step);
+
+        get_state(&mut self.first_moment, group, parameters.len());
+        get_state(&mut self.second_moment, group, parameters.len());
+        let first_moment = &mut self.first_moment[group];
+        let second_moment = &mut self.second_moment[group];
+
+        for i in 0..parameters.len() {
+            first_moment[i] = beta1 * first_moment[i] + (1.0 - beta1) * gradients[i];
+            second_moment[i] = beta2 * second_moment[i] + (1.0 - beta2) * gradients[i] * gradients[i];
+            let first_unbiased = first_moment[i] / correction1;
+            let second_unbiased = second_moment[i] / correction2;
+            parameters[i] -= learning_rate * first_unbiased / (second_unbiased.sqrt() + epsilon);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.step = 0;
+        self.first_moment.clear();
+        self.second_moment.clear();
+    }
+
+    //the step, followed by the first and then the second moment of every group
+    fn get_state(&self) -> Vec<Vec<f64>> {
+        let mut state = vec![vec![self.step as f64]];
+        state.extend(self.first_moment.iter().cloned());
+        state.extend(self.second_moment.iter().cloned());
+        state
+    }
+
+    fn set_state(&mut self, mut state: Vec<Vec<f64>>) {
+        self.reset();
+        if state.is_empty() {
+            return;
+        }
+        self.step = state[0].first().map_or(0, | &step | step as i32);
+        let mut moments = state.split_off(1);
+        let second_moment = moments.split_off(moments.len() / 2);
+        self.first_moment = moments;
+        self.second_moment = second_moment;
+    }
+}
+
+//two updates of a two value group with the gradients [0.5, -1] and then second_gradients
+#[cfg(test)]
+fn run_steps(optimizer: &mut dyn Optimizer, second_gradients: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let mut parameters = vec![1.0, -2.0];
+    optimizer.begin_update();
+    optimizer.update(0, &mut parameters, &[0.5, -1.0], 0.1);
+    let first = parameters.clone();
+    optimizer.begin_update();
+    optimizer.update(0, &mut parameters, second_gradients, 0.1);
+    (first, parameters)
+}
+
+#[cfg(test)]
+fn assert_close(values: &[f64], expected: &[f64]) {
+    for (&value, &expected) in values.iter().zip(expected.iter()) {
+        assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected);
+    }
+}
+
+#[test]
+fn test_optimizer_steps() {
+    let (first, second) = run_steps(&mut Sgd, &[0.5, -1.0]);
+    assert_close(&first, &[0.95, -1.9]);
+    assert_close(&second, &[0.9, -1.8]);
+
+    //v = 0.9 * v - 0.1 * g, p += v
+    let (first, second) = run_steps(&mut Momentum::new(0.9), &[0.5, -1.0]);
+    assert_close(&first, &[0.95, -1.9]);
+    assert_close(&second, &[0.855, -1.71]);
+
+    //v = 0.9 * v + g, p -= 0.1 * (g + 0.9 * v)
+    let (first, second) = run_steps(&mut Nesterov::new(0.9), &[0.5, -1.0]);
+    assert_close(&first, &[0.905, -1.81]);
+    assert_close(&second, &[0.7695, -1.539]);
+
+    //s = 0.9 * s + 0.1 * g^2, p -= 0.1 * g / (sqrt(s) + 1e-8)
+    let (first, second) = run_steps(&mut RmsProp::new(0.9), &[0.5, -1.0]);
+    assert_close(&first, &[1.0 - 0.05 / (0.025f64.sqrt() + 1e-8), -2.0 + 0.1 / (0.1f64.sqrt() + 1e-8)]);
+    assert_close(&second, &[first[0] - 0.05 / (0.0475f64.sqrt() + 1e-8), first[1] + 0.1 / (0.19f64.sqrt() + 1e-8)]);
+
+    //with bias correction the first step moves every parameter by about the learning rate,
+    //the second step uses m / (1 - 0.9^2) and v / (1 - 0.999^2)
+    let (first, second) = run_steps(&mut Adam::new(), &[1.0, 0.0]);
+    assert_close(&first, &[1.0 - 0.05 / (0.5 + 1e-8), -2.0 + 0.1 / (1.0 + 1e-8)]);
+    assert_close(&second, &[0.8034818006385094, -1.8329941765341886]);
+}
+
+#[test]
+fn test_optimizer_groups() {
+    //the state of a group is independent of the other groups
+    let mut optimizer = Momentum::new(0.9);
+    let (mut group0, mut group1) = (vec![1.0], vec![1.0, 1.0]);
+    optimizer.update(0, &mut group0, &[1.0], 0.1);
+    optimizer.update(1, &mut group1, &[-1.0, 0.0], 0.1);
+    optimizer.update(0, &mut group0, &[0.0], 0.1);
+    assert_close(&group0, &[1.0 - 0.1 - 0.09]);
+    assert_close(&group1, &[1.1, 1.0]);
+    assert!(optimizer.get_state().len() == 2);
+    assert_close(&optimizer.get_state()[0], &[-0.09]);
+    assert_close(&optimizer.get_state()[1], &[0.1, 0.0]);
+
+    let mut restored = Momentum::new(0.9);
+    restored.set_state(optimizer.get_state());
+    let mut parameters = group1.clone();
+    optimizer.update(1, &mut group1, &[0.5, 0.5], 0.1);
+    restored.update(1, &mut parameters, &[0.5, 0.5], 0.1);
+    assert!(group1 == parameters);
+}
+
+#[test]
+fn test_optimizer_from_name() {
+    //the parameter ends up in the state of the optimizer
+    let mut momentum = from_name("momentum:0.5").unwrap();
+    let mut parameters = vec![0.0];
+    momentum.update(0, &mut parameters, &[1.0], 1.0);
+    momentum.update(0, &mut parameters, &[0.0], 1.0);
+    assert!(momentum.get_state() == vec![vec![-0.5]]);
+
+    for name in ["sgd", "adam", "momentum", "nesterov:0.95", "rmsprop:0.99", "rmsprop:0"].iter() {
+        assert!(from_name(name).is_ok(), "{}", name);
+    }
+    for name in ["momentum:1", "nesterov:-0.1", "rmsprop:NaN", "momentum:fast", "sgd:0.5", "adam:0.5", "adam:", "lbfgs"].iter() {
+        assert!(from_name(name).is_err(), "{}", name);
+    }
+}
@@ -0,0 +1,87 @@
+use rand;
+use rand::{ Rng, SeedableRng, XorShiftRng };
+
+//xorshift is platform independent, so a seed gives the same sequence on every machine
+pub type Random = XorShiftRng;
+
+//splitmix64, spreads the bits of the seed, so similar seeds still give unrelated sequences
+fn splitmix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+pub fn create_rng(seed: u64) -> Random {
+    let mut state = seed;
+    let a = splitmix(&mut state);
+    let b = splitmix(&mut state);
+    let mut words = [a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32];
+
+    //xorshift must not be seeded with zeros only
+    if words.iter().all(| &e | e == 0) {
+        words[0] = 1;
+    }
+    XorShiftRng::from_seed(words)
+}
+
+//inverts value ^ (value >> shift)
+fn unshift_right(value: u32, shift: u32) -> u32 {
+    let mut result = value;
+    for _ in 0..32 / shift {
+        result = value ^ (result >> shift);
+    }
+    result
+}
+
+//inverts value ^ (value << shift)
+fn unshift_left(value: u32, shift: u32) -> u32 {
+    let mut result = value;
+    for _ in 0..32 / shift {
+        result = value ^ (result << shift);
+    }
+    result
+}
+
+//the internal state of the rng, from_state continues the identical sequence from it.
+//After four steps the state of xorshift consists of the last four outputs,
+//so the outputs of a clone are taken and the four steps are undone.
+pub fn get_state(rng: &Random) -> [u32; 4] {
+    let mut clone = rng.clone();
+    let mut state = [clone.next_u32(), clone.next_u32(), clone.next_u32(), clone.next_u32()];
+
+    for _ in 0..4 {
+        //w = w_prev ^ (w_prev >> 19) ^ t ^ (t >> 8), with t = x_prev ^ (x_prev << 11)
+        let [y, z, w_prev, w] = state;
+        let t = unshift_right(w ^ w_prev ^ (w_prev >> 19), 8);
+        state = [unshift_left(t, 11), y, z, w_prev];
+    }
+    state
+}
+
+pub fn from_state(state: [u32; 4]) -> Result<Random, String> {
+    match state.iter().all(| &e | e == 0) {
+        true => Err(String::from("The state of an rng must not be zero only")),
+        false => Ok(XorShiftRng::from_seed(state))
+    }
+}
+
+pub fn generate_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+#[test]
+fn test_rng_state() {
+    assert!(unshift_right(0xDEAD_BEEF ^ (0xDEAD_BEEF >> 8), 8) == 0xDEAD_BEEF);
+    assert!(unshift_left(0xDEAD_BEEF ^ (0xDEAD_BEEF << 11), 11) == 0xDEAD_BEEF);
+
+    let mut rng = create_rng(42);
+    for _ in 0..10 {
+        rng.next_u32();
+    }
+    let mut restored = from_state(get_state(&rng)).unwrap();
+    let sequence: Vec<u32> = (0..100).map(| _ | rng.next_u32()).collect();
+    assert!(sequence == (0..100).map(| _ | restored.next_u32()).collect::<Vec<u32>>());
+    assert!(from_state([0; 4]).is_err());
+}
@@ -0,0 +1,175 @@
+use std::f32::consts::PI;
+
+use color::Color;
+use renderer::Renderer;
+
+//Renders into an rgb pixel buffer in memory, without anti-aliasing.
+//A pixel is covered if its center is inside the shape. There is no font, text is not drawn.
+pub struct RasterRenderer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>
+}
+
+fn get_segment_distance(point: (f32, f32), from: (f32, f32), to: (f32, f32)) -> f32 {
+    let segment = (to.0 - from.0, to.1 - from.1);
+    let length_squared = segment.0 * segment.0 + segment.1 * segment.1;
+    let t = match length_squared > 0.0 {
+        true => (((point.0 - from.0) * segment.0 + (point.1 - from.1) * segment.1) / length_squared).clamp(0.0, 1.0),
+        false => 0.0
+    };
+    let nearest = (from.0 + segment.0 * t, from.1 + segment.1 * t);
+    ((point.0 - nearest.0).powi(2) + (point.1 - nearest.1).powi(2)).sqrt()
+}
+
+impl RasterRenderer {
+
+    pub fn new(width: usize, height: usize) -> RasterRenderer {
+        RasterRenderer {
+            width,
+            height,
+            pixels: vec![Color::from_rgb(0, 0, 0); width * height]
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    //row by row, starting at the top left
+    pub fn get_pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    //the pixels as rgb bytes, row by row
+    pub fn get_rgb_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.pixels.len() * 3);
+        for pixel in self.pixels.iter() {
+            let (r, g, b) = pixel.get_rgb();
+            bytes.extend_from_slice(&[r, g, b]);
+        }
+        bytes
+    }
+
+    //sets every pixel within the bounds whose center satisfies covers
+    fn fill<F: Fn((f32, f32)) -> bool>(&mut self, min: (f32, f32), max: (f32, f32), color: Color, covers: F) {
+        let min_x = min.0.floor().max(0.0) as usize;
+        let min_y = min.1.floor().max(0.0) as usize;
+        let max_x = (max.0.ceil().max(0.0) as usize).min(self.width);
+        let max_y = (max.1.ceil().max(0.0) as usize).min(self.height);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                if covers((x as f32 + 0.5, y as f32 + 0.5)) {
+                    self.pixels[y * self.width + x] = color;
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for RasterRenderer {
+
+    fn get_size(&self) -> (f32, f32) {
+        (self.width as f32, self.height as f32)
+    }
+
+    fn clear(&mut self, color: Color) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = color;
+        }
+    }
+
+    fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), color: Color, thickness: f32) {
+        //thin lines are still one pixel wide
+        let half = (thickness / 2.0).max(0.5);
+        self.fill((from.0.min(to.0) - half, from.1.min(to.1) - half),
+                  (from.0.max(to.0) + half, from.1.max(to.1) + half),
+                  color,
+                  | point | get_segment_distance(point, from, to) <= half);
+    }
+
+    fn draw_circle(&mut self, center: (f32, f32), radius: f32, color: Color, thickness: f32) {
+        self.draw_pieslice(center, radius, 0.0, 2.0 * PI, color, thickness);
+    }
+
+    fn draw_filled_circle(&mut self, center: (f32, f32), radius: f32, color: Color) {
+        self.fill((center.0 - radius, center.1 - radius),
+                  (center.0 + radius, center.1 + radius),
+                  color,
+                  | point | (point.0 - center.0).powi(2) + (point.1 - center.1).powi(2) <= radius * radius);
+    }
+
+    fn draw_pieslice(&mut self, center: (f32, f32), radius: f32, start_theta: f32, delta_theta: f32, color: Color, thickness: f32) {
+        let half = (thickness / 2.0).max(0.5);
+        let (start, delta) = match delta_theta < 0.0 {
+            true => (start_theta + delta_theta, -delta_theta),
+            false => (start_theta, delta_theta)
+        };
+
+        self.fill((center.0 - radius - half, center.1 - radius - half),
+                  (center.0 + radius + half, center.1 + radius + half),
+                  color,
+                  | point | {
+                      let offset = (point.0 - center.0, point.1 - center.1);
+                      let distance = (offset.0 * offset.0 + offset.1 * offset.1).sqrt();
+                      let angle = (offset.1.atan2(offset.0) - start).rem_euclid(2.0 * PI);
+                      (distance - radius).abs() <= half && (delta >= 2.0 * PI || angle <= delta)
+                  });
+
+        if delta < 2.0 * PI {
+            let point = | theta: f32 | (center.0 + radius * theta.cos(), center.1 + radius * theta.sin());
+            self.draw_line(center, point(start), color, thickness);
+            self.draw_line(center, point(start + delta), color, thickness);
+        }
+    }
+
+    fn draw_filled_rectangle(&mut self, from: (f32, f32), to: (f32, f32), color: Color) {
+        let min = (from.0.min(to.0), from.1.min(to.1));
+        let max = (from.0.max(to.0), from.1.max(to.1));
+        self.fill(min, max, color, | _ | true);
+    }
+
+    fn draw_text(&mut self, _pos: (f32, f32), _color: Color, _text: &str) {}
+}
+
+#[test]
+fn test_raster_renderer() {
+    use simulator::Simulator;
+    use renderer::{ render_simulation, get_background_color };
+
+    let mut renderer = RasterRenderer::new(40, 30);
+    renderer.clear(get_background_color());
+    let red = Color::from_rgb(0xFF, 0, 0);
+    renderer.draw_filled_circle((10.0, 10.0), 5.0, red);
+    assert!(renderer.get_pixel(10, 10) == red && renderer.get_pixel(14, 10) == red);
+    assert!(renderer.get_pixel(16, 10) == get_background_color());
+
+    renderer.draw_line((0.0, 25.5), (40.0, 25.5), red, 1.0);
+    assert!((0..40).all(| x | renderer.get_pixel(x, 25) == red));
+    assert!(renderer.get_pixel(20, 23) == get_background_color());
+
+    //a quarter slice to the lower right has its arc there, but not to the upper left
+    let white = Color::from_rgb(0xFF, 0xFF, 0xFF);
+    renderer.draw_pieslice((30.0, 10.0), 6.0, 0.0, PI / 2.0, white, 1.0);
+    assert!(renderer.get_pixel(34, 14) == white);
+    assert!(renderer.get_pixel(25, 5) != white);
+
+    //the bots of a rendered simulation are at their positions
+    let sim = Simulator::with_seed((800, 600), 10, 17).unwrap();
+    let mut renderer = RasterRenderer::new(400, 300);
+    render_simulation(&mut renderer, &sim, (0.0, 0.0), (0.5, 0.5));
+    for bot in sim.get_bots().borrow().iter() {
+        let pos = bot.get_pos();
+        assert!(renderer.get_pixel((pos.0 * 0.5) as usize, (pos.1 * 0.5) as usize) == bot.get_color());
+    }
+    assert!(renderer.get_rgb_bytes().len() == 400 * 300 * 3);
+}
@@ -1,4 +1,39 @@
+use std::f32;
 
+use utility::Vector2D;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HitObject {
+    Wall,
+    Food,
+    Bot
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    distance: f32,
+    object: HitObject
+}
+
+impl RayHit {
+
+    pub fn new(distance: f32, object: HitObject) -> RayHit {
+        RayHit {
+            distance,
+            object
+        }
+    }
+
+    pub fn get_distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn get_object(&self) -> HitObject {
+        self.object
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Ray{
     origin: (f32, f32),
     rotation: f32
@@ -8,9 +43,125 @@ impl Ray {
 
     pub fn new(origin: (f32, f32), rotation: f32) -> Ray {
         Ray {
-            origin: origin,
-            rotation: rotation
+            origin,
+            rotation
         }
     }
 
+    //count rays spread evenly over the field of view, every ray is at the center of its share of the fov
+    pub fn spread(origin: (f32, f32), rotation: f32, fov: f32, count: usize) -> Vec<Ray> {
+        (0..count)
+            .map(| i | Ray::new(origin, rotation - fov / 2.0 + fov * (i as f32 + 0.5) / count as f32))
+            .collect()
+    }
+
+    pub fn get_origin(&self) -> (f32, f32) {
+        self.origin
+    }
+
+    pub fn get_rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn get_direction(&self) -> (f32, f32) {
+        (self.rotation.cos(), self.rotation.sin())
+    }
+
+    pub fn get_point(&self, distance: f32) -> (f32, f32) {
+        let direction = self.get_direction();
+        (self.origin.0 + direction.0 * distance, self.origin.1 + direction.1 * distance)
+    }
+
+    //distance to the first point of the circle on the ray, 0 if the origin is inside the circle
+    pub fn intersect_circle(&self, center: (f32, f32), radius: f32) -> Option<f32> {
+        let direction = self.get_direction();
+        let offset = self.origin.sub(center);
+
+        let b = offset.dot(direction);
+        let c = offset.dot(offset) - radius * radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        match (-b - root, -b + root) {
+            (near, _) if near >= 0.0 => Some(near),
+            (_, far) if far >= 0.0 => Some(0.0),
+            _ => None
+        }
+    }
+
+    //distance to the border of the field, a rectangle from (0, 0) to field_size
+    pub fn intersect_field(&self, field_size: (f32, f32)) -> f32 {
+        let (x, y) = self.origin;
+        let (dir_x, dir_y) = self.get_direction();
+
+        let distance_x = match dir_x {
+            d if d > 0.0 => (field_size.0 - x) / d,
+            d if d < 0.0 => -x / d,
+            _ => f32::MAX
+        };
+        let distance_y = match dir_y {
+            d if d > 0.0 => (field_size.1 - y) / d,
+            d if d < 0.0 => -y / d,
+            _ => f32::MAX
+        };
+
+        distance_x.min(distance_y).max(0.0)
+    }
+
+    //the nearest wall, food or bot within max_distance,
+    //food and bots are given as position and radius
+    pub fn cast<F, B>(&self, max_distance: f32, field_size: (f32, f32), foods: F, bots: B) -> Option<RayHit>
+        where F: IntoIterator<Item = ((f32, f32), f32)>,
+              B: IntoIterator<Item = ((f32, f32), f32)> {
+        let mut nearest = RayHit::new(self.intersect_field(field_size), HitObject::Wall);
+
+        for (pos, size) in foods {
+            if let Some(distance) = self.intersect_circle(pos, size) {
+                if distance < nearest.distance {
+                    nearest = RayHit::new(distance, HitObject::Food);
+                }
+            }
+        }
+
+        for (pos, size) in bots {
+            if let Some(distance) = self.intersect_circle(pos, size) {
+                if distance < nearest.distance {
+                    nearest = RayHit::new(distance, HitObject::Bot);
+                }
+            }
+        }
+
+        match nearest.distance < max_distance {
+            true => Some(nearest),
+            false => None
+        }
+    }
+
+}
+
+#[test]
+fn test_ray_cast() {
+    let ray = Ray::new((10.0, 50.0), 0.0);
+
+    assert!(ray.intersect_circle((30.0, 50.0), 5.0) == Some(15.0));
+    assert!(ray.intersect_circle((30.0, 70.0), 5.0).is_none());
+    assert!(ray.intersect_circle((0.0, 50.0), 5.0).is_none());
+    assert!(ray.intersect_field((100.0, 100.0)) == 90.0);
+
+    let foods = vec![((60.0, 50.0), 5.0)];
+    let bots = vec![((40.0, 50.0), 10.0)];
+    let nothing = Vec::new();
+
+    let hit = ray.cast(100.0, (100.0, 100.0), foods.clone(), bots).unwrap();
+    assert!(hit == RayHit::new(20.0, HitObject::Bot));
+
+    let hit = ray.cast(100.0, (100.0, 100.0), foods, nothing.clone()).unwrap();
+    assert!(hit == RayHit::new(45.0, HitObject::Food));
+
+    let hit = ray.cast(100.0, (100.0, 100.0), nothing.clone(), nothing.clone()).unwrap();
+    assert!(hit == RayHit::new(90.0, HitObject::Wall));
+    assert!(ray.cast(50.0, (100.0, 100.0), nothing.clone(), nothing).is_none());
 }
@@ -0,0 +1,254 @@
+use std::collections::VecDeque;
+use std::f64;
+
+use rand::Rng;
+
+use neuralnet::NeuralNet;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LearningMode {
+    //imitation of the expected output of the teacher
+    Supervised,
+    //q-learning from rewards, every output of the network is the value of one action
+    QLearning(QLearningConfig)
+}
+
+impl LearningMode {
+
+    //"supervised" or "qlearning", which uses the default configuration
+    pub fn from_name(name: &str) -> Result<LearningMode, String> {
+        match name {
+            "supervised" => Ok(LearningMode::Supervised),
+            "qlearning" => Ok(LearningMode::QLearning(QLearningConfig::new())),
+            _ => Err(format!("Unknown learning mode \"{}\"", name))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QLearningConfig {
+    discount: f64,
+    learning_rate: f64,
+    exploration: f64,
+    exploration_min: f64,
+    exploration_decay: f64,
+    memory_size: usize,
+    batch_size: usize
+}
+
+impl Default for QLearningConfig {
+    fn default() -> QLearningConfig {
+        QLearningConfig::new()
+    }
+}
+
+impl QLearningConfig {
+
+    pub fn new() -> QLearningConfig {
+        QLearningConfig {
+            discount: 0.95,
+            learning_rate: 0.01,
+            exploration: 1.0,
+            exploration_min: 0.05,
+            exploration_decay: 0.999,
+            memory_size: 2000,
+            batch_size: 16
+        }
+    }
+
+    pub fn discount(mut self, discount: f64) -> Self {
+        self.discount = discount;
+        self
+    }
+
+    pub fn learning_rate(mut self, learning_rate: f64) -> Self {
+        self.learning_rate = learning_rate;
+        self
+    }
+
+    //chance of a random action, decays by exploration_decay per action down to exploration_min
+    pub fn exploration(mut self, exploration: f64, exploration_min: f64, exploration_decay: f64) -> Self {
+        self.exploration = exploration;
+        self.exploration_min = exploration_min;
+        self.exploration_decay = exploration_decay;
+        self
+    }
+
+    pub fn memory_size(mut self, memory_size: usize) -> Self {
+        assert!(memory_size > 0);
+        self.memory_size = memory_size;
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0);
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn get_learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+}
+
+//next_state is None for the last transition of a bot's life
+struct Transition {
+    state: Vec<f64>,
+    action: usize,
+    reward: f64,
+    next_state: Option<Vec<f64>>
+}
+
+struct ReplayMemory {
+    transitions: VecDeque<Transition>,
+    capacity: usize
+}
+
+impl ReplayMemory {
+
+    fn new(capacity: usize) -> ReplayMemory {
+        ReplayMemory {
+            transitions: VecDeque::with_capacity(capacity),
+            capacity
+        }
+    }
+
+    fn push(&mut self, transition: Transition) {
+        if self.transitions.len() >= self.capacity {
+            self.transitions.pop_front();
+        }
+        self.transitions.push_back(transition);
+    }
+
+    fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    fn get(&self, index: usize) -> &Transition {
+        &self.transitions[index]
+    }
+}
+
+//The q-learning state of a single bot.
+//The reward of an action is collected until the next action is selected,
+//then the transition is stored and the network is trained on a batch of the replay memory.
+pub struct QLearner {
+    config: QLearningConfig,
+    exploration: f64,
+    memory: ReplayMemory,
+    last_action: Option<(Vec<f64>, usize)>,
+    pending_reward: f64
+}
+
+fn argmax(values: &[f64]) -> usize {
+    let mut best = 0;
+    for i in 1..values.len() {
+        if values[i] > values[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+impl QLearner {
+
+    pub fn new(config: QLearningConfig) -> QLearner {
+        QLearner {
+            config,
+            exploration: config.exploration,
+            memory: ReplayMemory::new(config.memory_size),
+            last_action: None,
+            pending_reward: 0.0
+        }
+    }
+
+    pub fn get_config(&self) -> &QLearningConfig {
+        &self.config
+    }
+
+    pub fn get_exploration(&self) -> f64 {
+        self.exploration
+    }
+
+    //the chance of a random action, e.g. when a snapshot is restored
+    pub fn set_exploration(&mut self, exploration: f64) {
+        assert!((0.0..=1.0).contains(&exploration));
+        self.exploration = exploration;
+    }
+
+    pub fn add_reward(&mut self, reward: f64) {
+        self.pending_reward += reward;
+    }
+
+    //stores the outcome of the former action, trains and selects the next action for state,
+    //returns the action and the training error
+    pub fn select_action<R: Rng>(&mut self, nn: &mut NeuralNet, state: &[f64], rng: &mut R) -> (usize, f64) {
+        let mut error = 0.0;
+        if let Some((last_state, last_action)) = self.last_action.take() {
+            self.memory.push(Transition {
+                state: last_state,
+                action: last_action,
+                reward: self.pending_reward,
+                next_state: Some(state.to_vec())
+            });
+            error = self.train(nn, rng);
+        }
+        self.pending_reward = 0.0;
+
+        //evaluated even when exploring, so the network shows its response to the current state
+        let values = nn.feed_forward(state);
+        let action = match rng.gen::<f64>() < self.exploration {
+            true => rng.gen_range(0, nn.get_output_size()),
+            false => argmax(&values)
+        };
+        self.exploration = (self.exploration * self.config.exploration_decay).max(self.config.exploration_min);
+
+        self.last_action = Some((state.to_vec(), action));
+        (action, error)
+    }
+
+    //ends the life of the bot with a final reward
+    pub fn finish<R: Rng>(&mut self, nn: &mut NeuralNet, reward: f64, rng: &mut R) {
+        if let Some((last_state, last_action)) = self.last_action.take() {
+            self.memory.push(Transition {
+                state: last_state,
+                action: last_action,
+                reward: self.pending_reward + reward,
+                next_state: None
+            });
+            self.train(nn, rng);
+        }
+        self.pending_reward = 0.0;
+    }
+
+    //one batch update on randomly sampled transitions, returns the average error
+    fn train<R: Rng>(&mut self, nn: &mut NeuralNet, rng: &mut R) -> f64 {
+        if self.memory.len() < self.config.batch_size {
+            return 0.0;
+        }
+
+        //the states were observed by the input normalization when they were selected on
+        let frozen = nn.is_normalization_frozen();
+        nn.set_normalization_frozen(true);
+
+        let mut total_error = 0.0;
+        for _ in 0..self.config.batch_size {
+            let transition = self.memory.get(rng.gen_range(0, self.memory.len()));
+
+            let mut target_value = transition.reward;
+            if let Some(ref next_state) = transition.next_state {
+                let next_values = nn.feed_forward(next_state);
+                target_value += self.config.discount * next_values.iter().fold(f64::MIN, | max, &e | max.max(e));
+            }
+
+            //only the taken action gets a gradient, all other outputs are their own target
+            let mut target = nn.feed_forward(&transition.state);
+            target[transition.action] = target_value;
+            total_error += nn.accumulate_gradients(&target);
+        }
+        nn.apply_gradients(self.config.learning_rate);
+        nn.set_normalization_frozen(frozen);
+
+        total_error / self.config.batch_size as f64
+    }
+}
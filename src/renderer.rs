@@ -0,0 +1,110 @@
+//Drawing independent of a graphics library. Positions and sizes are in pixels of the target,
+//angles in radians, clockwise from the x axis as y points down.
+
+use bot::Bot;
+use food::{ Food, get_default_color };
+use color::Color;
+use simulator::Simulator;
+use replay::{ ReplayBot, ReplayFood };
+
+pub trait Renderer {
+    fn get_size(&self) -> (f32, f32);
+    fn clear(&mut self, color: Color);
+    fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), color: Color, thickness: f32);
+    fn draw_circle(&mut self, center: (f32, f32), radius: f32, color: Color, thickness: f32);
+    fn draw_filled_circle(&mut self, center: (f32, f32), radius: f32, color: Color);
+    //the outline of a circle sector, from start_theta over delta_theta
+    fn draw_pieslice(&mut self, center: (f32, f32), radius: f32, start_theta: f32, delta_theta: f32, color: Color, thickness: f32);
+    fn draw_filled_rectangle(&mut self, from: (f32, f32), to: (f32, f32), color: Color);
+    //pos is the upper left corner of the text
+    fn draw_text(&mut self, pos: (f32, f32), color: Color, text: &str);
+    //a description of the next primitive, e.g. shown when hovering it in a browser,
+    //renderers without interaction ignore it
+    fn set_tooltip(&mut self, _text: &str) {}
+}
+
+//Something that is drawn onto the field, camera_pos is the field position shown in the upper left corner
+//and scale the pixels per field unit.
+pub trait Drawable {
+    fn draw(&self, renderer: &mut dyn Renderer, camera_pos: (f32, f32), scale: (f32, f32));
+}
+
+pub fn get_background_color() -> Color {
+    Color::from_rgb(22, 22, 22)
+}
+
+pub fn get_border_color() -> Color {
+    Color::from_rgb(0xFF, 0, 0)
+}
+
+fn to_view(pos: (f32, f32), camera_pos: (f32, f32), scale: (f32, f32)) -> (f32, f32) {
+    ((pos.0 - camera_pos.0) * scale.0, (pos.1 - camera_pos.1) * scale.1)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_bot(renderer: &mut dyn Renderer, pos: (f32, f32), rot: f32, size: f32, view_radius: f32, fov: f32, color: Color, camera_pos: (f32, f32), scale: (f32, f32)) {
+    let center = to_view(pos, camera_pos, scale);
+    renderer.draw_pieslice(center, view_radius * scale.0, rot - fov / 2.0, fov, Color::from_rgb(0xFF, 0xFF, 0xFF), 1.0);
+    renderer.draw_filled_circle(center, size * scale.0, color);
+}
+
+impl Drawable for Bot {
+    fn draw(&self, renderer: &mut dyn Renderer, camera_pos: (f32, f32), scale: (f32, f32)) {
+        draw_bot(renderer, self.get_pos(), self.get_rotation(), self.get_size(), self.get_view_radius(), self.get_fov(), self.get_color(), camera_pos, scale);
+    }
+}
+
+impl Drawable for ReplayBot {
+    fn draw(&self, renderer: &mut dyn Renderer, camera_pos: (f32, f32), scale: (f32, f32)) {
+        draw_bot(renderer, self.pos, self.rot, self.size, self.view_radius, self.fov, self.color, camera_pos, scale);
+    }
+}
+
+impl Drawable for Food {
+    fn draw(&self, renderer: &mut dyn Renderer, camera_pos: (f32, f32), scale: (f32, f32)) {
+        renderer.draw_filled_circle(to_view(self.get_pos(), camera_pos, scale), self.get_size() * scale.0, self.get_color());
+    }
+}
+
+impl Drawable for ReplayFood {
+    fn draw(&self, renderer: &mut dyn Renderer, camera_pos: (f32, f32), scale: (f32, f32)) {
+        //replays don't store the color, all food looks alike
+        renderer.draw_filled_circle(to_view(self.pos, camera_pos, scale), self.size * scale.0, get_default_color());
+    }
+}
+
+//the borders of a field of field_size
+pub fn draw_field_border(renderer: &mut dyn Renderer, field_size: (f32, f32), camera_pos: (f32, f32), scale: (f32, f32)) {
+    const BORDER_THICKNESS: f32 = 2.0;
+
+    let (left, top) = to_view((0.0, 0.0), camera_pos, scale);
+    let (right, bottom) = to_view(field_size, camera_pos, scale);
+    //inset by half the thickness, so the borders stay visible at the edge of the target
+    let (left, top) = (left + BORDER_THICKNESS / 2.0, top + BORDER_THICKNESS / 2.0);
+    let (right, bottom) = (right - BORDER_THICKNESS / 2.0, bottom - BORDER_THICKNESS / 2.0);
+
+    renderer.draw_line((left, top), (right, top), get_border_color(), BORDER_THICKNESS);
+    renderer.draw_line((left, bottom), (right, bottom), get_border_color(), BORDER_THICKNESS);
+    renderer.draw_line((left, top), (left, bottom), get_border_color(), BORDER_THICKNESS);
+    renderer.draw_line((right, top), (right, bottom), get_border_color(), BORDER_THICKNESS);
+}
+
+//the whole field with all food and bots, bots out of the renderer's view are skipped
+pub fn render_simulation(renderer: &mut dyn Renderer, simulator: &Simulator, camera_pos: (f32, f32), scale: (f32, f32)) {
+    renderer.clear(get_background_color());
+
+    for food in simulator.get_foods().borrow().iter() {
+        food.draw(renderer, camera_pos, scale);
+    }
+
+    let size = renderer.get_size();
+    for bot in simulator.get_bots().borrow().iter() {
+        let (x, y) = to_view(bot.get_pos(), camera_pos, scale);
+        let reach = bot.get_view_radius() * scale.0;
+        if x + reach >= 0.0 && y + reach >= 0.0 && x - reach < size.0 && y - reach < size.1 {
+            bot.draw(renderer, camera_pos, scale);
+        }
+    }
+
+    draw_field_border(renderer, simulator.get_field_size(), camera_pos, scale);
+}
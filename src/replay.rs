@@ -0,0 +1,482 @@
+//Recording of a simulation as an event log, which can be played back without running any network.
+//
+//Text format (version 1), whitespace separated, one record per line:
+//  replay 1
+//  field <width> <height>
+//  tick <tick>                 all following events happen in this tick
+//  spawn <id> <x> <y> <rotation> <size> <view radius> <fov> <energy> <r> <g> <b>
+//  food <x> <y> <size> <energy>
+//  eat <bot id> <food x> <food y>
+//  death <bot id> <cause>
+//  state <bot id> <x> <y> <rotation> <energy> <action> ...
+//The first tick holds spawn and food events for the whole world at the start of the recording.
+//Within a tick the events are in the order of the simulation: eating, new food, the state of every bot
+//after it moved, deaths and finally new bots.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{ Read, Write, BufReader, BufWriter };
+use std::path::Path;
+
+use bot::{ Bot, DeathCause };
+use food::Food;
+use color::Color;
+use serialization::{ io_error, next_record, parse_fields };
+
+pub const REPLAY_VERSION: u32 = 1;
+const REPLAY_HEADER: &str = "replay";
+//a full state is kept every KEYFRAME_INTERVAL ticks, seeking applies the events after the nearest one
+const KEYFRAME_INTERVAL: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplayEvent {
+    Spawn(ReplayBot),
+    Food(ReplayFood),
+    Eat(u64, (f32, f32)),
+    Death(u64, DeathCause),
+    State(u64, (f32, f32), f32, u32, Vec<f64>)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayBot {
+    pub id: u64,
+    pub pos: (f32, f32),
+    pub rot: f32,
+    pub size: f32,
+    pub view_radius: f32,
+    pub fov: f32,
+    pub color: Color,
+    pub energy: u32,
+    pub actions: Vec<f64>
+}
+
+impl ReplayBot {
+
+    pub fn from_bot(bot: &Bot) -> ReplayBot {
+        ReplayBot {
+            id: bot.get_id(),
+            pos: bot.get_pos(),
+            rot: bot.get_rotation(),
+            size: bot.get_size(),
+            view_radius: bot.get_view_radius(),
+            fov: bot.get_fov(),
+            color: bot.get_color(),
+            energy: bot.get_energy(),
+            actions: bot.get_actions().to_vec()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReplayFood {
+    pub pos: (f32, f32),
+    pub size: f32,
+    pub energy: u32
+}
+
+impl ReplayFood {
+
+    pub fn from_food(food: &Food) -> ReplayFood {
+        ReplayFood {
+            pos: food.get_pos(),
+            size: food.get_size(),
+            energy: food.get_energy()
+        }
+    }
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values.iter().map(| e | e.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+impl ReplayEvent {
+
+    fn to_line(&self) -> String {
+        match *self {
+            ReplayEvent::Spawn(ref bot) => {
+                let (r, g, b) = bot.color.get_rgb();
+                format!("spawn {} {} {} {} {} {} {} {} {} {} {}\n", bot.id, bot.pos.0, bot.pos.1, bot.rot, bot.size, bot.view_radius, bot.fov, bot.energy, r, g, b)
+            },
+            ReplayEvent::Food(ref food) => format!("food {} {} {} {}\n", food.pos.0, food.pos.1, food.size, food.energy),
+            ReplayEvent::Eat(id, pos) => format!("eat {} {} {}\n", id, pos.0, pos.1),
+            ReplayEvent::Death(id, cause) => format!("death {} {}\n", id, cause.get_name()),
+            ReplayEvent::State(id, pos, rot, energy, ref actions) => match actions.is_empty() {
+                true => format!("state {} {} {} {} {}\n", id, pos.0, pos.1, rot, energy),
+                false => format!("state {} {} {} {} {} {}\n", id, pos.0, pos.1, rot, energy, join(actions))
+            }
+        }
+    }
+
+    fn from_fields(fields: &[&str]) -> Result<ReplayEvent, String> {
+        let values = &fields[1..];
+        match fields[0] {
+            "spawn" => {
+                if values.len() != 11 {
+                    return Err(format!("Expected 11 values for spawn, got {}", values.len()));
+                }
+                let id = parse_fields::<u64>(&values[0..1], 1, "bot id")?[0];
+                let geometry = parse_fields::<f32>(&values[1..7], 6, "spawn")?;
+                let energy = parse_fields::<u32>(&values[7..8], 1, "energy")?[0];
+                let rgb = parse_fields::<u8>(&values[8..11], 3, "spawn color")?;
+                Ok(ReplayEvent::Spawn(ReplayBot {
+                    id,
+                    pos: (geometry[0], geometry[1]),
+                    rot: geometry[2],
+                    size: geometry[3],
+                    view_radius: geometry[4],
+                    fov: geometry[5],
+                    color: Color::from_rgb(rgb[0], rgb[1], rgb[2]),
+                    energy,
+                    actions: Vec::new()
+                }))
+            },
+            "food" => {
+                if values.len() != 4 {
+                    return Err(format!("Expected 4 values for food, got {}", values.len()));
+                }
+                let geometry = parse_fields::<f32>(&values[0..3], 3, "food")?;
+                let energy = parse_fields::<u32>(&values[3..4], 1, "food energy")?[0];
+                Ok(ReplayEvent::Food(ReplayFood { pos: (geometry[0], geometry[1]), size: geometry[2], energy }))
+            },
+            "eat" => {
+                if values.len() != 3 {
+                    return Err(format!("Expected 3 values for eat, got {}", values.len()));
+                }
+                let id = parse_fields::<u64>(&values[0..1], 1, "bot id")?[0];
+                let pos = parse_fields::<f32>(&values[1..3], 2, "food position")?;
+                Ok(ReplayEvent::Eat(id, (pos[0], pos[1])))
+            },
+            "death" => {
+                if values.len() != 2 {
+                    return Err(format!("Expected 2 values for death, got {}", values.len()));
+                }
+                let id = parse_fields::<u64>(&values[0..1], 1, "bot id")?[0];
+                Ok(ReplayEvent::Death(id, DeathCause::from_name(values[1])?))
+            },
+            "state" => {
+                if values.len() < 5 {
+                    return Err(format!("Expected at least 5 values for state, got {}", values.len()));
+                }
+                let id = parse_fields::<u64>(&values[0..1], 1, "bot id")?[0];
+                let geometry = parse_fields::<f32>(&values[1..4], 3, "state")?;
+                let energy = parse_fields::<u32>(&values[4..5], 1, "energy")?[0];
+                let actions = parse_fields::<f64>(&values[5..], values.len() - 5, "actions")?;
+                Ok(ReplayEvent::State(id, (geometry[0], geometry[1]), geometry[2], energy, actions))
+            },
+            name => Err(format!("Unknown replay event \"{}\"", name))
+        }
+    }
+}
+
+//Streams the events of a simulation to a writer.
+//Write errors stop the recording, the first error is kept until finish.
+pub struct Recorder {
+    writer: Box<dyn Write>,
+    error: Option<String>
+}
+
+impl Recorder {
+
+    pub fn new(mut writer: Box<dyn Write>, field_size: (f32, f32)) -> Recorder {
+        let header = format!("{} {}\nfield {} {}\n", REPLAY_HEADER, REPLAY_VERSION, field_size.0, field_size.1);
+        let error = writer.write_all(header.as_bytes()).err().map(io_error);
+
+        Recorder {
+            writer,
+            error
+        }
+    }
+
+    pub fn create(path: &Path, field_size: (f32, f32)) -> Result<Recorder, String> {
+        let file = File::create(path).map_err(io_error)?;
+        Ok(Recorder::new(Box::new(BufWriter::new(file)), field_size))
+    }
+
+    pub fn begin_tick(&mut self, tick: u64) {
+        self.write(&format!("tick {}\n", tick));
+    }
+
+    pub fn record(&mut self, event: &ReplayEvent) {
+        self.write(&event.to_line());
+    }
+
+    //flushes the writer, returns the first error of the recording
+    pub fn finish(mut self) -> Result<(), String> {
+        if self.error.is_none() {
+            self.error = self.writer.flush().err().map(io_error);
+        }
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(())
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.error.is_none() {
+            self.error = self.writer.write_all(text.as_bytes()).err().map(io_error);
+        }
+    }
+}
+
+//The world at one tick of a replay.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayState {
+    tick: u64,
+    bots: BTreeMap<u64, ReplayBot>,
+    food: Vec<ReplayFood>,
+    deaths: Vec<(u64, DeathCause)>
+}
+
+impl ReplayState {
+
+    pub fn get_tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn get_bots(&self) -> Vec<&ReplayBot> {
+        self.bots.values().collect()
+    }
+
+    pub fn get_bot(&self, id: u64) -> Option<&ReplayBot> {
+        self.bots.get(&id)
+    }
+
+    pub fn get_food(&self) -> &[ReplayFood] {
+        &self.food
+    }
+
+    //the bots that died in this tick
+    pub fn get_deaths(&self) -> &[(u64, DeathCause)] {
+        &self.deaths
+    }
+
+    fn apply(&mut self, tick: u64, events: &[ReplayEvent]) {
+        self.tick = tick;
+        self.deaths.clear();
+
+        for event in events {
+            match *event {
+                ReplayEvent::Spawn(ref bot) => {
+                    self.bots.insert(bot.id, bot.clone());
+                },
+                ReplayEvent::Food(food) => self.food.push(food),
+                ReplayEvent::Eat(id, pos) => {
+                    if let Some(index) = self.food.iter().position(| food | food.pos == pos) {
+                        let food = self.food.swap_remove(index);
+                        if let Some(bot) = self.bots.get_mut(&id) {
+                            bot.energy += food.energy;
+                        }
+                    }
+                },
+                ReplayEvent::Death(id, cause) => {
+                    self.bots.remove(&id);
+                    self.deaths.push((id, cause));
+                },
+                ReplayEvent::State(id, pos, rot, energy, ref actions) => {
+                    if let Some(bot) = self.bots.get_mut(&id) {
+                        bot.pos = pos;
+                        bot.rot = rot;
+                        bot.energy = energy;
+                        bot.actions = actions.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+//A loaded recording, which can be positioned at any of its ticks.
+pub struct Replay {
+    field_size: (f32, f32),
+    ticks: Vec<(u64, Vec<ReplayEvent>)>,
+    keyframes: Vec<ReplayState>,
+    position: usize,
+    state: ReplayState
+}
+
+impl Replay {
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let file = File::open(path).map_err(io_error)?;
+        Replay::read(&mut BufReader::new(file))
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> Result<Replay, String> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).map_err(io_error)?;
+
+        let mut lines = text.lines()
+            .filter(| line | !line.trim().is_empty())
+            .map(| line | line.split_whitespace().collect::<Vec<&str>>());
+        let lines = &mut lines;
+
+        let version = parse_fields::<u32>(&next_record(lines, REPLAY_HEADER)?, 1, "replay version")?[0];
+        if version != REPLAY_VERSION {
+            return Err(format!("Unsupported replay version {} (supported: {})", version, REPLAY_VERSION));
+        }
+        let field_size = parse_fields::<f32>(&next_record(lines, "field")?, 2, "field")?;
+
+        let mut ticks: Vec<(u64, Vec<ReplayEvent>)> = Vec::new();
+        for fields in lines {
+            if fields[0] == "tick" {
+                let tick = parse_fields::<u64>(&fields[1..], 1, "tick")?[0];
+                ticks.push((tick, Vec::new()));
+                continue;
+            }
+            match ticks.last_mut() {
+                Some(&mut (_, ref mut events)) => events.push(ReplayEvent::from_fields(&fields)?),
+                None => return Err(String::from("Expected a tick before the first event"))
+            }
+        }
+        if ticks.is_empty() {
+            return Err(String::from("The replay holds no ticks"));
+        }
+
+        let mut state = ReplayState::default();
+        let mut keyframes = Vec::new();
+        for (index, &(tick, ref events)) in ticks.iter().enumerate() {
+            state.apply(tick, events);
+            if index % KEYFRAME_INTERVAL == 0 {
+                keyframes.push(state.clone());
+            }
+        }
+
+        //the first keyframe is the state at the first tick
+        let state = keyframes[0].clone();
+        Ok(Replay {
+            field_size: (field_size[0], field_size[1]),
+            ticks,
+            keyframes,
+            position: 0,
+            state
+        })
+    }
+
+    pub fn get_field_size(&self) -> (f32, f32) {
+        self.field_size
+    }
+
+    pub fn get_state(&self) -> &ReplayState {
+        &self.state
+    }
+
+    pub fn get_first_tick(&self) -> u64 {
+        self.ticks[0].0
+    }
+
+    pub fn get_last_tick(&self) -> u64 {
+        self.ticks[self.ticks.len() - 1].0
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position == self.ticks.len() - 1
+    }
+
+    //moves to the last recorded tick at or before tick
+    pub fn seek(&mut self, tick: u64) {
+        let index = match self.ticks.binary_search_by_key(&tick, | &(tick, _) | tick) {
+            Ok(index) => index,
+            Err(0) => 0,
+            Err(index) => index - 1
+        };
+        self.seek_index(index);
+    }
+
+    //moves by count recorded ticks, negative counts move backwards
+    pub fn step(&mut self, count: i64) {
+        let index = (self.position as i64 + count).max(0).min(self.ticks.len() as i64 - 1);
+        self.seek_index(index as usize);
+    }
+
+    fn seek_index(&mut self, index: usize) {
+        //stepping forward by one only needs the events of the next tick
+        if index == self.position + 1 {
+            let (tick, ref events) = self.ticks[index];
+            self.state.apply(tick, events);
+        }
+        else if index != self.position || self.state.tick != self.ticks[index].0 {
+            let keyframe = index / KEYFRAME_INTERVAL;
+            self.state = self.keyframes[keyframe].clone();
+            for &(tick, ref events) in self.ticks[keyframe * KEYFRAME_INTERVAL + 1..index + 1].iter() {
+                self.state.apply(tick, events);
+            }
+        }
+        self.position = index;
+    }
+}
+
+#[cfg(test)]
+use std::rc::Rc;
+#[cfg(test)]
+use std::cell::RefCell;
+#[cfg(test)]
+use simulator::Simulator;
+
+//a writer that shares its buffer with the test
+#[cfg(test)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+#[cfg(test)]
+impl Write for SharedBuffer {
+    fn write(&mut self, data: &[u8]) -> ::std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(data);
+        Ok(data.len())
+    }
+    fn flush(&mut self) -> ::std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_record_and_replay() {
+    let buffer = Rc::new(RefCell::new(Vec::new()));
+    let mut sim = Simulator::with_seed((800, 600), 10, 3).unwrap();
+    sim.fast_forward(50);
+    let recorder = Recorder::new(Box::new(SharedBuffer(buffer.clone())), sim.get_field_size());
+    sim.start_recording(recorder);
+    sim.fast_forward(250);
+
+    let positions = | sim: &Simulator | {
+        let mut positions: Vec<(u64, (f32, f32))> = sim.get_bots().borrow().iter().map(| bot | (bot.get_id(), bot.get_pos())).collect();
+        positions.sort_by_key(| &(id, _) | id);
+        positions
+    };
+    let expected = positions(&sim);
+    let expected_food = sim.get_foods().borrow().len();
+    sim.stop_recording().unwrap();
+
+    let mut replay = Replay::read(&mut &buffer.borrow()[..]).unwrap();
+    assert!(replay.get_first_tick() == 50 && replay.get_last_tick() == 300);
+
+    replay.seek(300);
+    let replayed: Vec<(u64, (f32, f32))> = replay.get_state().get_bots().iter().map(| bot | (bot.id, bot.pos)).collect();
+    assert!(replayed == expected);
+    assert!(replay.get_state().get_food().len() == expected_food);
+
+    //scrubbing backwards and stepping forward again reaches the same state
+    replay.seek(120);
+    let at_120: Vec<ReplayBot> = replay.get_state().get_bots().into_iter().cloned().collect();
+    replay.step(-30);
+    replay.step(30);
+    assert!(replay.get_state().get_tick() == 120);
+    assert!(replay.get_state().get_bots().into_iter().cloned().collect::<Vec<_>>() == at_120);
+}
+
+#[test]
+fn test_replay_from_first_tick() {
+    //a fresh simulator records its initial world at tick 0
+    let buffer = Rc::new(RefCell::new(Vec::new()));
+    let mut sim = Simulator::with_seed((800, 600), 10, 5).unwrap();
+    sim.start_recording(Recorder::new(Box::new(SharedBuffer(buffer.clone())), sim.get_field_size()));
+    let mut expected: Vec<(u64, (f32, f32))> = sim.get_bots().borrow().iter().map(| bot | (bot.get_id(), bot.get_pos())).collect();
+    expected.sort_by_key(| &(id, _) | id);
+    let expected_food = sim.get_foods().borrow().len();
+    sim.fast_forward(20);
+    sim.stop_recording().unwrap();
+
+    let replay = Replay::read(&mut &buffer.borrow()[..]).unwrap();
+    assert!(replay.get_first_tick() == 0 && replay.get_state().get_tick() == 0);
+    let replayed: Vec<(u64, (f32, f32))> = replay.get_state().get_bots().iter().map(| bot | (bot.id, bot.pos)).collect();
+    assert!(replayed == expected && !replayed.is_empty());
+    assert!(replay.get_state().get_food().len() == expected_food && expected_food > 0);
+}
@@ -0,0 +1,232 @@
+use std::f32;
+use std::f64::consts::PI;
+
+use rand::Rng;
+
+use bot::Bot;
+use food::Food;
+use environment::Environment;
+use ray::{ Ray, HitObject };
+use normalization::Normalization;
+use spatialgrid::SpatialGrid;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sensor {
+    //distance and angle of the nearest visible food
+    NearestFood,
+    //distance and angle of the nearest visible point of a wall
+    NearestWall,
+    //distance and angle of the nearest visible bot
+    NearestBot,
+    //remaining energy
+    Energy,
+    //distance moved in the last tick
+    Speed,
+    //n rays spread evenly over the field of view,
+    //per ray the hit distance followed by a one-hot wall, food and bot flag, all zero if nothing is in view
+    Rays(usize),
+    //uniform random value in [-1, 1)
+    Noise
+}
+
+impl Sensor {
+
+    pub fn get_size(&self) -> usize {
+        match *self {
+            Sensor::NearestFood | Sensor::NearestWall | Sensor::NearestBot => 2,
+            Sensor::Energy | Sensor::Speed | Sensor::Noise => 1,
+            Sensor::Rays(count) => count * RAY_SIZE
+        }
+    }
+
+    pub fn get_name(&self) -> String {
+        match *self {
+            Sensor::NearestFood => String::from("nearest_food"),
+            Sensor::NearestWall => String::from("nearest_wall"),
+            Sensor::NearestBot => String::from("nearest_bot"),
+            Sensor::Energy => String::from("energy"),
+            Sensor::Speed => String::from("speed"),
+            Sensor::Rays(count) => format!("rays:{}", count),
+            Sensor::Noise => String::from("noise")
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Sensor, String> {
+        match name {
+            "nearest_food" => Ok(Sensor::NearestFood),
+            "nearest_wall" => Ok(Sensor::NearestWall),
+            "nearest_bot" => Ok(Sensor::NearestBot),
+            "energy" => Ok(Sensor::Energy),
+            "speed" => Ok(Sensor::Speed),
+            "noise" => Ok(Sensor::Noise),
+            _ if name.starts_with("rays:") => match name["rays:".len()..].parse::<usize>() {
+                Ok(count) if count > 0 => Ok(Sensor::Rays(count)),
+                _ => Err(format!("Invalid ray count in \"{}\"", name))
+            },
+            _ => Err(format!("Unknown sensor \"{}\"", name))
+        }
+    }
+
+    //one normalization per input, scaling distances and angles to [-1, 1]
+    pub fn get_default_normalization(&self, view_radius: f64) -> Vec<Normalization> {
+        let distance = Normalization::Range(0.0, view_radius);
+        let angle = Normalization::Range(-PI, PI);
+
+        match *self {
+            Sensor::NearestFood | Sensor::NearestWall | Sensor::NearestBot => vec![distance, angle],
+            Sensor::Energy | Sensor::Speed => vec![Normalization::Standardize],
+            Sensor::Rays(count) => (0..count)
+                .flat_map(| _ | vec![distance, Normalization::None, Normalization::None, Normalization::None])
+                .collect(),
+            Sensor::Noise => vec![Normalization::None]
+        }
+    }
+
+    //the inputs the bots were designed with, the rules of the default teacher depend on them
+    pub fn default_suite() -> Vec<Sensor> {
+        vec![Sensor::NearestWall, Sensor::NearestFood]
+    }
+}
+
+const RAY_SIZE: usize = 4;
+
+pub fn get_input_size(sensors: &[Sensor]) -> usize {
+    sensors.iter().fold(0, | sum, sensor | sum + sensor.get_size())
+}
+
+//What the sensors of a bot can perceive. Bot positions are taken before any bot moves in a tick,
+//so the order in which bots are processed does not change what they see.
+pub struct World<'a> {
+    pub field_size: (f32, f32),
+    pub boundaries: &'a [((f32, f32), f32)],
+    pub food: &'a [Food],
+    pub food_grid: &'a SpatialGrid,
+    //position and size of every bot
+    pub bots: &'a [((f32, f32), f32)],
+    pub bot_grid: &'a SpatialGrid
+}
+
+//reads all sensors of the bot, bot_index is the bot's index in bots
+pub fn create_environment<R: Rng>(bot: &Bot, bot_index: usize, world: &World, rng: &mut R) -> Environment {
+    let mut environment = Environment::new(bot.get_sensors());
+
+    for (index, sensor) in bot.get_sensors().iter().enumerate() {
+        let values = match *sensor {
+            Sensor::NearestFood => sense_nearest_point(bot, nearby_food(bot, world).map(| (pos, _) | pos)),
+            Sensor::NearestWall => sense_nearest_wall(bot, world),
+            Sensor::NearestBot => sense_nearest_point(bot, nearby_bots(bot, bot_index, world).map(| (pos, _) | pos)),
+            Sensor::Energy => vec![bot.get_energy() as f64],
+            Sensor::Speed => vec![bot.get_velocity() as f64],
+            Sensor::Rays(count) => sense_rays(bot, bot_index, count, world),
+            Sensor::Noise => vec![rng.gen_range(-1.0, 1.0)]
+        };
+        environment.set_sensor_input(index, &values);
+    }
+
+    environment
+}
+
+fn nearby_food<'a>(bot: &Bot, world: &'a World) -> impl Iterator<Item = ((f32, f32), f32)> + 'a {
+    world.food_grid.query(bot.get_pos(), bot.get_view_radius())
+        .into_iter()
+        .map(move | index | (world.food[index].get_pos(), world.food[index].get_size()))
+}
+
+//all bots in view range except the bot itself
+fn nearby_bots<'a>(bot: &Bot, bot_index: usize, world: &'a World) -> impl Iterator<Item = ((f32, f32), f32)> + 'a {
+    world.bot_grid.query(bot.get_pos(), bot.get_view_radius())
+        .into_iter()
+        .filter(move | &index | index != bot_index)
+        .map(move | index | world.bots[index])
+}
+
+fn nearest_or_zero(nearest: (f32, f32)) -> Vec<f64> {
+    match nearest.0 < f32::MAX {
+        true => vec![nearest.0 as f64, nearest.1 as f64],
+        false => vec![0.0, 0.0]
+    }
+}
+
+fn sense_nearest_point<I: Iterator<Item = (f32, f32)>>(bot: &Bot, points: I) -> Vec<f64> {
+    let nearest = points
+        .filter_map(| point | bot.sees_point(point))
+        .fold((f32::MAX, 0.0), | min_val, e | match e.0 < min_val.0 { true => e, false => min_val });
+    nearest_or_zero(nearest)
+}
+
+fn sense_nearest_wall(bot: &Bot, world: &World) -> Vec<f64> {
+    let nearest = world.boundaries.iter()
+        .filter_map(| boundary | bot.sees_line(*boundary))
+        .fold((f32::MAX, 0.0), | min_val, e | match e.0 < min_val.0 { true => e, false => min_val });
+    nearest_or_zero(nearest)
+}
+
+fn sense_rays(bot: &Bot, bot_index: usize, count: usize, world: &World) -> Vec<f64> {
+    let mut values = Vec::with_capacity(count * RAY_SIZE);
+
+    let foods: Vec<((f32, f32), f32)> = nearby_food(bot, world).collect();
+    let bots: Vec<((f32, f32), f32)> = nearby_bots(bot, bot_index, world).collect();
+
+    for ray in Ray::spread(bot.get_pos(), bot.get_rotation(), bot.get_fov(), count) {
+        match ray.cast(bot.get_view_radius(), world.field_size, foods.iter().cloned(), bots.iter().cloned()) {
+            Some(hit) => {
+                values.push(hit.get_distance() as f64);
+                values.push(if hit.get_object() == HitObject::Wall { 1.0 } else { 0.0 });
+                values.push(if hit.get_object() == HitObject::Food { 1.0 } else { 0.0 });
+                values.push(if hit.get_object() == HitObject::Bot { 1.0 } else { 0.0 });
+            },
+            None => values.extend_from_slice(&[0.0; RAY_SIZE])
+        }
+    }
+
+    values
+}
+
+#[test]
+fn test_sensor_layout() {
+    use random::create_rng;
+
+    let sensors = vec![Sensor::NearestFood, Sensor::Energy, Sensor::Rays(5), Sensor::Noise];
+    assert!(get_input_size(&sensors) == 24);
+
+    for sensor in sensors.iter() {
+        assert!(Sensor::from_name(&sensor.get_name()) == Ok(*sensor));
+    }
+    assert!(Sensor::from_name("rays:0").is_err());
+    for sensor in sensors.iter() {
+        assert!(sensor.get_default_normalization(100.0).len() == sensor.get_size());
+    }
+
+    let mut rng = create_rng(1);
+    let mut bot = Bot::new(&[24, 3], 10.0, 5.0, &mut rng);
+    bot.set_sensors(sensors);
+    bot.set_pos((50.0, 50.0));
+
+    //a food straight ahead, the bot itself is not seen by its own rays
+    let food = [Food::new((80.0, 50.0), 2.0, 100)];
+    let bots = [(bot.get_pos(), bot.get_size())];
+    let mut food_grid = SpatialGrid::new((100.0, 100.0), 30.0);
+    food_grid.rebuild(food.iter().map(| food | (food.get_pos(), food.get_size())));
+    let mut bot_grid = SpatialGrid::new((100.0, 100.0), 30.0);
+    bot_grid.rebuild(bots.iter().cloned());
+
+    let world = World {
+        field_size: (100.0, 100.0),
+        boundaries: &[],
+        food: &food,
+        food_grid: &food_grid,
+        bots: &bots,
+        bot_grid: &bot_grid
+    };
+    let environment = create_environment(&bot, 0, &world, &mut rng);
+    assert!(environment.get_input().len() == 24);
+    assert!(environment.get_sensor_input(Sensor::Energy) == Some(&[1000.0][..]));
+    assert!(environment.get_sensor_input(Sensor::NearestBot).is_none());
+
+    let rays = environment.get_sensor_input(Sensor::Rays(5)).unwrap();
+    assert!((rays[8] - 28.0).abs() < 1e-3 && rays[9..12] == [0.0, 1.0, 0.0]);
+    for ray in [0, 1, 3, 4].iter() {
+        let values = &rays[ray * RAY_SIZE..(ray + 1) * RAY_SIZE];
+        assert!((50.0..100.0).contains(&values[0]) && values[1] == 1.0);
+    }
+}
@@ -0,0 +1,440 @@
+//Persistence of trained networks.
+//
+//Text format (version 2), whitespace separated, one record per line:
+//  neuralnet 2
+//  topology <units of layer 0> <units of layer 1> ...
+//  activation <activation of layer 0> <activation of layer 1> ...
+//  loss <loss>
+//  normalization <normalizer of input 0> <normalizer of input 1> ...
+//  weight <layer>              followed by one line per unit of layer,
+//                              holding the weights to every unit of layer + 1
+//  bias <layer>                followed by one line with the bias of every unit of layer, for layer >= 1
+//  end
+//Activations, losses and normalizers are stored by name, e.g. "sigmoid", "leaky_relu:0.01", "huber:1"
+//or "standardize:<count>:<mean>:<square sum>", which includes the running statistics.
+//Numbers are written with the shortest representation that reads back to the identical value.
+//
+//Binary format (version 2), all integers and floats little endian:
+//  magic "NNET", version u32, layer count u32, units u32 per layer,
+//  activation name per layer, the loss name and the normalizer name per input, each as length u8 + ascii bytes,
+//  weights f64 per layer in the order of the text format, biases f64 of the layers >= 1,
+//  FNV-1a u32 checksum of all preceding bytes.
+//
+//Version 1 is the same without normalization, its inputs are not normalized.
+
+use std::fs::File;
+use std::io::{ Read, Write, BufRead, BufReader, BufWriter };
+use std::path::Path;
+
+use neuralnet::NeuralNet;
+use activation::Activation;
+use loss::Loss;
+use normalization::Normalizer;
+
+pub const FORMAT_VERSION: u32 = 2;
+const TEXT_HEADER: &str = "neuralnet";
+const BINARY_MAGIC: &[u8; 4] = b"NNET";
+//rejects topologies of corrupt or hostile files before anything is allocated for them
+const MAX_PARAMETER_COUNT: usize = 1 << 24;
+
+pub fn io_error<E: ToString>(e: E) -> String {
+    String::from("I/O error: ") + &e.to_string()
+}
+
+fn fnv1a(data: &[u8]) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+    for &byte in data {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+fn check_version(version: u32) -> Result<(), String> {
+    match version {
+        1..=FORMAT_VERSION => Ok(()),
+        _ => Err(format!("Unsupported format version {} (supported: 1 to {})", version, FORMAT_VERSION))
+    }
+}
+
+//creates the network from a validated topology and activations, loss and parameters still need to be set
+fn create_net(topology: &[usize], activations: &[Activation]) -> Result<NeuralNet, String> {
+    if topology.len() < 2 || topology.contains(&0) {
+        return Err(format!("Invalid topology {:?}", topology));
+    }
+    if activations.len() != topology.len() {
+        return Err(format!("Expected {} activations, got {}", topology.len(), activations.len()));
+    }
+    let parameter_count = topology.windows(2).try_fold(0usize, | sum, pair | {
+        pair[0].checked_mul(pair[1])
+            .and_then(| weights | weights.checked_add(pair[1]))
+            .and_then(| parameters | sum.checked_add(parameters))
+    });
+    match parameter_count {
+        Some(count) if count <= MAX_PARAMETER_COUNT => {},
+        _ => return Err(format!("Topology {:?} exceeds {} parameters", topology, MAX_PARAMETER_COUNT))
+    }
+
+    let mut nn = NeuralNet::new(topology);
+    for (layer, &activation) in activations.iter().enumerate().skip(1) {
+        nn.set_activation(layer, activation);
+    }
+    Ok(nn)
+}
+
+impl NeuralNet {
+
+    pub fn save_text(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(io_error)?;
+        self.write_text(&mut BufWriter::new(file))
+    }
+
+    pub fn load_text(path: &Path) -> Result<NeuralNet, String> {
+        let file = File::open(path).map_err(io_error)?;
+        NeuralNet::read_text(&mut BufReader::new(file))
+    }
+
+    pub fn save_binary(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(io_error)?;
+        self.write_binary(&mut BufWriter::new(file))
+    }
+
+    pub fn load_binary(path: &Path) -> Result<NeuralNet, String> {
+        let file = File::open(path).map_err(io_error)?;
+        NeuralNet::read_binary(&mut BufReader::new(file))
+    }
+
+    pub fn write_text<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        let topology = self.get_topology();
+        let mut text = format!("{} {}\n", TEXT_HEADER, FORMAT_VERSION);
+
+        text += &format!("topology {}\n", topology.iter().map(| units | units.to_string()).collect::<Vec<_>>().join(" "));
+        text += &format!("activation {}\n", (0..topology.len()).map(| layer | self.get_activation(layer).get_name()).collect::<Vec<_>>().join(" "));
+        text += &format!("loss {}\n", self.get_loss().get_name());
+        text += &format!("normalization {}\n", (0..topology[0]).map(| input | self.get_normalizer(input).get_name()).collect::<Vec<_>>().join(" "));
+
+        for layer in 0..topology.len() - 1 {
+            text += &format!("weight {}\n", layer);
+            for unit in 0..topology[layer] {
+                let row: Vec<String> = (0..topology[layer + 1]).map(| dest | self.get_weight(layer, unit, dest).to_string()).collect();
+                text += &row.join(" ");
+                text += "\n";
+            }
+        }
+
+        for (layer, &units) in topology.iter().enumerate().skip(1) {
+            text += &format!("bias {}\n", layer);
+            let row: Vec<String> = (0..units).map(| unit | self.get_bias(layer, unit).to_string()).collect();
+            text += &row.join(" ");
+            text += "\n";
+        }
+        text += "end\n";
+
+        writer.write_all(text.as_bytes()).map_err(io_error)
+    }
+
+    pub fn read_text<R: BufRead>(reader: &mut R) -> Result<NeuralNet, String> {
+        let mut lines = Vec::new();
+        for line in reader.lines() {
+            let line = line.map_err(io_error)?;
+            if !line.trim().is_empty() {
+                lines.push(line);
+            }
+        }
+        let mut lines = lines.iter().map(| line | line.split_whitespace().collect::<Vec<&str>>());
+        NeuralNet::read_text_records(&mut lines)
+    }
+
+    //reads a network from lines split into fields, stops after the end record,
+    //so a network can be embedded in other text formats
+    pub fn read_text_records<'a, I: Iterator<Item = Vec<&'a str>>>(lines: &mut I) -> Result<NeuralNet, String> {
+        let header = next_record(lines, TEXT_HEADER)?;
+        let version = parse_fields::<u32>(&header, 1, "version")?[0];
+        check_version(version)?;
+
+        let topology_fields = next_record(lines, "topology")?;
+        let topology = parse_fields::<usize>(&topology_fields, topology_fields.len(), "topology")?;
+
+        let mut activations = Vec::new();
+        for name in next_record(lines, "activation")? {
+            activations.push(Activation::from_name(name)?);
+        }
+
+        let mut nn = create_net(&topology, &activations)?;
+
+        let loss = next_record(lines, "loss")?;
+        if loss.len() != 1 {
+            return Err(String::from("Expected exactly one loss"));
+        }
+        nn.set_loss(Loss::from_name(loss[0])?);
+
+        if version >= 2 {
+            let names = next_record(lines, "normalization")?;
+            if names.len() != topology[0] {
+                return Err(format!("Expected {} normalizers, got {}", topology[0], names.len()));
+            }
+            for (input, name) in names.iter().enumerate() {
+                nn.set_normalizer(input, Normalizer::from_name(name)?);
+            }
+        }
+
+        for layer in 0..topology.len() - 1 {
+            let index = parse_fields::<usize>(&next_record(lines, "weight")?, 1, "weight layer")?[0];
+            if index != layer {
+                return Err(format!("Expected weights of layer {}, found layer {}", layer, index));
+            }
+            for unit in 0..topology[layer] {
+                let row = match lines.next() {
+                    Some(fields) => parse_fields::<f64>(&fields, topology[layer + 1], "weight")?,
+                    None => return Err(String::from("Unexpected end of data in weights"))
+                };
+                for (dest, &value) in row.iter().enumerate() {
+                    nn.set_weight(value, layer, unit, dest);
+                }
+            }
+        }
+
+        for (layer, &units) in topology.iter().enumerate().skip(1) {
+            let index = parse_fields::<usize>(&next_record(lines, "bias")?, 1, "bias layer")?[0];
+            if index != layer {
+                return Err(format!("Expected bias of layer {}, found layer {}", layer, index));
+            }
+            let row = match lines.next() {
+                Some(fields) => parse_fields::<f64>(&fields, units, "bias")?,
+                None => return Err(String::from("Unexpected end of data in bias"))
+            };
+            for (unit, &value) in row.iter().enumerate() {
+                nn.set_bias(value, layer, unit);
+            }
+        }
+
+        next_record(lines, "end")?;
+        Ok(nn)
+    }
+
+    pub fn write_binary<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        let topology = self.get_topology();
+        let mut data: Vec<u8> = Vec::new();
+
+        data.extend_from_slice(BINARY_MAGIC);
+        data.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        data.extend_from_slice(&(topology.len() as u32).to_le_bytes());
+        for &units in topology {
+            data.extend_from_slice(&(units as u32).to_le_bytes());
+        }
+
+        for layer in 0..topology.len() {
+            write_name(&mut data, &self.get_activation(layer).get_name());
+        }
+        write_name(&mut data, &self.get_loss().get_name());
+        for input in 0..topology[0] {
+            write_name(&mut data, &self.get_normalizer(input).get_name());
+        }
+
+        for layer in 0..topology.len() - 1 {
+            for unit in 0..topology[layer] {
+                for dest in 0..topology[layer + 1] {
+                    data.extend_from_slice(&self.get_weight(layer, unit, dest).to_le_bytes());
+                }
+            }
+        }
+        for (layer, &units) in topology.iter().enumerate().skip(1) {
+            for unit in 0..units {
+                data.extend_from_slice(&self.get_bias(layer, unit).to_le_bytes());
+            }
+        }
+
+        let checksum = fnv1a(&data);
+        data.extend_from_slice(&checksum.to_le_bytes());
+
+        writer.write_all(&data).map_err(io_error)
+    }
+
+    pub fn read_binary<R: Read>(reader: &mut R) -> Result<NeuralNet, String> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(io_error)?;
+
+        if data.len() < 8 || &data[0..4] != BINARY_MAGIC {
+            return Err(String::from("Not a binary neural net file"));
+        }
+
+        let mut cursor = ByteCursor { data: &data[4..], pos: 0 };
+        let version = cursor.read_u32()?;
+        check_version(version)?;
+
+        if data.len() < 12 {
+            return Err(String::from("Unexpected end of data"));
+        }
+        let (content, checksum) = data.split_at(data.len() - 4);
+        let mut checksum_bytes = [0u8; 4];
+        checksum_bytes.copy_from_slice(checksum);
+        if fnv1a(content) != u32::from_le_bytes(checksum_bytes) {
+            return Err(String::from("Checksum mismatch, the data is corrupted"));
+        }
+
+        let mut cursor = ByteCursor { data: &content[8..], pos: 0 };
+
+        let layers = cursor.read_u32()? as usize;
+        let mut topology = Vec::new();
+        for _ in 0..layers {
+            topology.push(cursor.read_u32()? as usize);
+        }
+
+        let mut activations = Vec::new();
+        for _ in 0..layers {
+            activations.push(Activation::from_name(&cursor.read_name()?)?);
+        }
+
+        let mut nn = create_net(&topology, &activations)?;
+        nn.set_loss(Loss::from_name(&cursor.read_name()?)?);
+        if version >= 2 {
+            for input in 0..topology[0] {
+                nn.set_normalizer(input, Normalizer::from_name(&cursor.read_name()?)?);
+            }
+        }
+
+        for layer in 0..layers - 1 {
+            for unit in 0..topology[layer] {
+                for dest in 0..topology[layer + 1] {
+                    let value = cursor.read_f64()?;
+                    nn.set_weight(value, layer, unit, dest);
+                }
+            }
+        }
+        for (layer, &units) in topology.iter().enumerate().skip(1) {
+            for unit in 0..units {
+                let value = cursor.read_f64()?;
+                nn.set_bias(value, layer, unit);
+            }
+        }
+
+        if !cursor.is_empty() {
+            return Err(String::from("Unexpected trailing data"));
+        }
+        Ok(nn)
+    }
+}
+
+pub fn next_record<'a, I: Iterator<Item = Vec<&'a str>>>(lines: &mut I, name: &str) -> Result<Vec<&'a str>, String> {
+    match lines.next() {
+        Some(ref fields) if !fields.is_empty() && fields[0] == name => Ok(fields[1..].to_vec()),
+        Some(fields) => Err(format!("Expected \"{}\", found \"{}\"", name, fields.join(" "))),
+        None => Err(format!("Unexpected end of data, expected \"{}\"", name))
+    }
+}
+
+pub fn parse_fields<T: ::std::str::FromStr>(fields: &[&str], count: usize, name: &str) -> Result<Vec<T>, String> {
+    if fields.len() != count {
+        return Err(format!("Expected {} values for {}, got {}", count, name, fields.len()));
+    }
+    let mut values = Vec::new();
+    for field in fields {
+        match field.parse::<T>() {
+            Ok(value) => values.push(value),
+            Err(_) => return Err(format!("Invalid value \"{}\" for {}", field, name))
+        }
+    }
+    Ok(values)
+}
+
+fn write_name(data: &mut Vec<u8>, name: &str) {
+    assert!(name.len() <= u8::MAX as usize);
+    data.push(name.len() as u8);
+    data.extend_from_slice(name.as_bytes());
+}
+
+struct ByteCursor<'a> {
+    data: &'a [u8],
+    pos: usize
+}
+
+impl<'a> ByteCursor<'a> {
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if self.pos + count > self.data.len() {
+            return Err(String::from("Unexpected end of data"));
+        }
+        let bytes = &self.data[self.pos..self.pos + count];
+        self.pos += count;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_f64(&mut self) -> Result<f64, String> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(f64::from_le_bytes(bytes))
+    }
+
+    fn read_name(&mut self) -> Result<String, String> {
+        let len = self.take(1)?[0] as usize;
+        match String::from_utf8(self.take(len)?.to_vec()) {
+            Ok(name) => Ok(name),
+            Err(_) => Err(String::from("Invalid name encoding"))
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+}
+
+#[cfg(test)]
+use random::create_rng;
+#[cfg(test)]
+use normalization::Normalization;
+
+#[test]
+fn test_roundtrip() {
+    let mut nn = NeuralNet::new(&[3, 5, 2]);
+    nn.randomize(-1.0, 1.0, &mut create_rng(1));
+    nn.set_activation(1, Activation::LeakyRelu(0.01));
+    nn.set_output_activation(Activation::Softmax);
+    nn.set_loss(Loss::CategoricalCrossEntropy);
+    nn.set_input_normalization(&[Normalization::None, Normalization::Range(-1.0, 1.0), Normalization::Standardize]);
+
+    let input = vec![0.3, -0.2, 0.9];
+    nn.feed_forward(&[0.1, 0.5, -0.7]);
+    nn.set_normalization_frozen(true);
+    let expected = nn.feed_forward(&input);
+
+    let mut text = Vec::new();
+    nn.write_text(&mut text).unwrap();
+    let mut from_text = NeuralNet::read_text(&mut &text[..]).unwrap();
+    from_text.set_normalization_frozen(true);
+    assert!(from_text.feed_forward(&input) == expected);
+
+    let mut binary = Vec::new();
+    nn.write_binary(&mut binary).unwrap();
+    let mut from_binary = NeuralNet::read_binary(&mut &binary[..]).unwrap();
+    from_binary.set_normalization_frozen(true);
+    assert!(from_binary.feed_forward(&input) == expected);
+    assert!(from_binary.get_loss() == Loss::CategoricalCrossEntropy);
+    assert!(from_binary.get_normalizer(2) == nn.get_normalizer(2));
+
+    //version 1 has no normalization record
+    let text = String::from_utf8(text).unwrap()
+        .replace("neuralnet 2", "neuralnet 1")
+        .lines()
+        .filter(| line | !line.starts_with("normalization"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let from_version_1 = NeuralNet::read_text(&mut text.as_bytes()).unwrap();
+    assert!(from_version_1.get_normalizer(2).get_normalization() == Normalization::None);
+
+    binary[20] ^= 0xFF;
+    assert!(NeuralNet::read_binary(&mut &binary[..]).is_err());
+}
+
+#[test]
+fn test_hostile_topology() {
+    let text = "neuralnet 2\ntopology 100000 100000\nactivation linear sigmoid\n";
+    assert!(NeuralNet::read_text(&mut text.as_bytes()).is_err());
+    let text = format!("neuralnet 2\ntopology 2 {} 2\nactivation linear sigmoid sigmoid\n", usize::MAX);
+    assert!(NeuralNet::read_text(&mut text.as_bytes()).is_err());
+}
@@ -1,16 +1,33 @@
 use std::result::Result;
-use std::cell::RefCell;
+use std::cell::{ RefCell, Ref };
 use std::string::String;
 use std::f32;
 use std::f32::consts::PI;
 
-use rand;
 use rand::distributions::{ Range, IndependentSample };
 
-use bot::Bot;
-use environment::Environment;
+use bot::{ Bot, DeathCause, VIEW_RADIUS_FACTOR };
+use neuralnet::NeuralNet;
+use evolution::Evolution;
+use reinforcement::{ LearningMode, QLearner };
+use normalization::Normalization;
+use teacher::{ Teacher, RuleTeacher, Event };
+use sensor::{ Sensor, World, create_environment, get_input_size };
 use food::Food;
 use utility::get_distance;
+use spatialgrid::SpatialGrid;
+use replay::{ Recorder, ReplayEvent, ReplayBot, ReplayFood };
+use statistics::{ Statistics, TickStatistics };
+
+use random::{ Random, create_rng, generate_seed };
+
+//the input layer is sized by the sensors, the outputs are the actions of a bot
+const BOT_HIDDEN_TOPOLOGY: [usize; 2] = [4, 4];
+const BOT_OUTPUT_SIZE: usize = 3;
+const BOT_SIZE: f32 = 10.0;
+const BOT_SPEED: f32 = 5.0;
+//as large as the view radius of a bot, so a view query covers at most 3x3 cells
+const GRID_CELL_SIZE: f32 = BOT_SIZE * VIEW_RADIUS_FACTOR;
 
 pub struct Simulator {
     bots: RefCell<Vec<Bot>>,
@@ -20,11 +37,31 @@ pub struct Simulator {
     field_size: (f32, f32),
     boundaries: [((f32, f32), f32); 4],
     ticks: u64,
+    seed: u64,
+    rng: Random,
+    evolution: Option<Evolution>,
+    learning_mode: LearningMode,
+    teacher: Box<dyn Teacher>,
+    sensors: Vec<Sensor>,
+    //per sensor, None uses the default normalization of the sensor
+    normalization: Vec<Option<Normalization>>,
+    food_grid: SpatialGrid,
+    bot_grid: SpatialGrid,
+    recorder: Option<Recorder>,
+    next_bot_id: u64,
+    statistics: Statistics,
+    //the statistics of the running tick
+    tick_statistics: TickStatistics,
 }
 
 impl Simulator {
 
     pub fn new(field_size: (i32, i32), bot_count: u32) -> Result<Simulator, String> {
+        Simulator::with_seed(field_size, bot_count, generate_seed())
+    }
+
+    //the same seed gives the same simulation
+    pub fn with_seed(field_size: (i32, i32), bot_count: u32, seed: u64) -> Result<Simulator, String> {
 
         static FOOD_COUNT: u32 = 50;
 
@@ -40,9 +77,22 @@ impl Simulator {
             min_bot_count: bot_count,
             food: RefCell::new(Vec::new()),
             food_count: FOOD_COUNT,
-            field_size: field_size,
-            boundaries: boundaries,
+            field_size,
+            boundaries,
             ticks: 0,
+            seed,
+            rng: create_rng(seed),
+            evolution: None,
+            learning_mode: LearningMode::Supervised,
+            teacher: Box::new(RuleTeacher::new()),
+            sensors: Sensor::default_suite(),
+            normalization: vec![None; Sensor::default_suite().len()],
+            food_grid: SpatialGrid::new(field_size, GRID_CELL_SIZE),
+            bot_grid: SpatialGrid::new(field_size, GRID_CELL_SIZE),
+            recorder: None,
+            next_bot_id: 0,
+            statistics: Statistics::new(),
+            tick_statistics: TickStatistics::new(0),
         };
 
         sim.spawn_bots(bot_count);
@@ -55,10 +105,60 @@ impl Simulator {
         &self.bots
     }
 
+    //the id of the bot nearest to pos, among the bots within their size plus margin of it
+    pub fn find_bot_at(&self, pos: (f32, f32), margin: f32) -> Option<u64> {
+        self.bots.borrow().iter()
+            .map(| bot | (bot.get_id(), get_distance(pos, bot.get_pos()) - bot.get_size()))
+            .filter(| &(_, distance) | distance <= margin)
+            .fold(None, | nearest: Option<(u64, f32)>, (id, distance) | match nearest {
+                Some((_, nearest_distance)) if nearest_distance <= distance => nearest,
+                _ => Some((id, distance))
+            })
+            .map(| (id, _) | id)
+    }
+
+    pub fn get_bot(&self, id: u64) -> Option<Ref<'_, Bot>> {
+        Ref::filter_map(self.bots.borrow(), | bots | bots.iter().find(| bot | bot.get_id() == id)).ok()
+    }
+
     pub fn get_foods(&self) -> &RefCell<Vec<Food>> {
         &self.food
     }
 
+    //the food by index, as of the last sensor update
+    pub fn get_food_grid(&self) -> &SpatialGrid {
+        &self.food_grid
+    }
+
+    //the bots by index, as of the last sensor update
+    pub fn get_bot_grid(&self) -> &SpatialGrid {
+        &self.bot_grid
+    }
+
+    //the population below which new bots are spawned
+    pub fn get_bot_count(&self) -> u32 {
+        self.min_bot_count
+    }
+
+    //spawns bots to reach count, a lower count lets the population shrink by dying bots
+    pub fn set_bot_count(&mut self, count: u32) {
+        self.min_bot_count = count;
+        let missing = (count as usize).saturating_sub(self.bots.borrow().len());
+        self.spawn_bots(missing as u32);
+    }
+
+    pub fn get_food_count(&self) -> u32 {
+        self.food_count
+    }
+
+    //spawns or removes food to reach count
+    pub fn set_food_count(&mut self, count: u32) {
+        self.food_count = count;
+        self.food.borrow_mut().truncate(count as usize);
+        let missing = count as usize - self.food.borrow().len();
+        self.spawn_foods(missing as u32);
+    }
+
     pub fn get_field_size(&self) -> (f32, f32) {
         self.field_size
     }
@@ -67,6 +167,145 @@ impl Simulator {
         self.ticks
     }
 
+    pub fn set_ticks(&mut self, ticks: u64) {
+        self.ticks = ticks;
+    }
+
+    //the random sequence of the simulation, at its current position
+    pub fn get_rng(&self) -> &Random {
+        &self.rng
+    }
+
+    //continues the simulation with another random sequence, the initial seed stays as it is
+    pub fn set_rng(&mut self, rng: Random) {
+        self.rng = rng;
+    }
+
+    //gives the bot a learner, if the simulation is in a reinforcement learning mode
+    pub fn attach_learner(&self, bot: &mut Bot) {
+        if let LearningMode::QLearning(config) = self.learning_mode {
+            bot.set_learner(Some(QLearner::new(config)));
+        }
+    }
+
+    //the id the next spawned bot gets
+    pub fn get_next_bot_id(&self) -> u64 {
+        self.next_bot_id
+    }
+
+    pub fn set_next_bot_id(&mut self, id: u64) {
+        self.next_bot_id = id;
+    }
+
+    //records the events of all following ticks, starting with the current world
+    pub fn start_recording(&mut self, mut recorder: Recorder) {
+        recorder.begin_tick(self.ticks);
+        for bot in self.bots.borrow().iter() {
+            recorder.record(&ReplayEvent::Spawn(ReplayBot::from_bot(bot)));
+        }
+        for food in self.food.borrow().iter() {
+            recorder.record(&ReplayEvent::Food(ReplayFood::from_food(food)));
+        }
+        self.recorder = Some(recorder);
+    }
+
+    //ends the recording, returns the first error which occurred while recording
+    pub fn stop_recording(&mut self) -> Result<(), String> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish(),
+            None => Ok(())
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    fn record(&mut self, event: ReplayEvent) {
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.record(&event);
+        }
+    }
+
+    //the statistics of every tick so far
+    pub fn get_statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    pub fn get_statistics_mut(&mut self) -> &mut Statistics {
+        &mut self.statistics
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    //with evolution, new bots are bred from the fittest bots instead of being random
+    pub fn set_evolution(&mut self, evolution: Option<Evolution>) {
+        self.evolution = evolution;
+    }
+
+    pub fn get_evolution(&self) -> Option<&Evolution> {
+        self.evolution.as_ref()
+    }
+
+    pub fn get_evolution_mut(&mut self) -> Option<&mut Evolution> {
+        self.evolution.as_mut()
+    }
+
+    //applies to bots spawned from now on
+    pub fn set_learning_mode(&mut self, learning_mode: LearningMode) {
+        self.learning_mode = learning_mode;
+    }
+
+    pub fn get_learning_mode(&self) -> LearningMode {
+        self.learning_mode
+    }
+
+    pub fn set_teacher(&mut self, teacher: Box<dyn Teacher>) {
+        self.teacher = teacher;
+    }
+
+    pub fn get_teacher(&self) -> &dyn Teacher {
+        &*self.teacher
+    }
+
+    //applies to bots spawned from now on, resets the normalization to the defaults of the sensors
+    pub fn set_sensors(&mut self, sensors: Vec<Sensor>) {
+        assert!(!sensors.is_empty());
+        self.normalization = vec![None; sensors.len()];
+        self.sensors = sensors;
+    }
+
+    //applies the normalization to every input of the sensor at sensor_index, None restores the default
+    pub fn set_sensor_normalization(&mut self, sensor_index: usize, normalization: Option<Normalization>) {
+        self.normalization[sensor_index] = normalization;
+    }
+
+    //the normalization of every input of the bot networks
+    pub fn get_input_normalization(&self) -> Vec<Normalization> {
+        let view_radius = (BOT_SIZE * VIEW_RADIUS_FACTOR) as f64;
+        let mut input_normalization = Vec::new();
+        for (sensor, normalization) in self.sensors.iter().zip(self.normalization.iter()) {
+            match *normalization {
+                Some(normalization) => input_normalization.extend(vec![normalization; sensor.get_size()]),
+                None => input_normalization.extend(sensor.get_default_normalization(view_radius))
+            }
+        }
+        input_normalization
+    }
+
+    pub fn get_sensors(&self) -> &[Sensor] {
+        &self.sensors
+    }
+
+    pub fn get_bot_topology(&self) -> Vec<usize> {
+        let mut topology = vec![get_input_size(&self.sensors)];
+        topology.extend_from_slice(&BOT_HIDDEN_TOPOLOGY);
+        topology.push(BOT_OUTPUT_SIZE);
+        topology
+    }
+
     pub fn fast_forward(&mut self, cycles: u32) {
         for _ in 0..cycles {
             self.cycle();
@@ -74,8 +313,30 @@ impl Simulator {
     }
 
     pub fn spawn_bot(&mut self) {
-        let mut bot = Bot::new(4, 4, 10.0, 5.0);
-        bot.randomize_pos_rot(self.field_size);
+        let topology = self.get_bot_topology();
+        let child = match self.evolution {
+            Some(ref evolution) => evolution.breed(&NeuralNet::new(&topology), &self.bots.borrow(), &mut self.rng),
+            None => None
+        };
+
+        let mut nn = match child {
+            Some(nn) => nn,
+            None => {
+                let mut nn = NeuralNet::new(&topology);
+                nn.randomize(-1.0, 1.0, &mut self.rng);
+                nn
+            }
+        };
+        nn.set_input_normalization(&self.get_input_normalization());
+
+        let mut bot = Bot::with_net(nn, BOT_SIZE, BOT_SPEED, &mut self.rng);
+        bot.set_sensors(self.sensors.clone());
+        bot.randomize_pos_rot(self.field_size, &mut self.rng);
+        self.attach_learner(&mut bot);
+        bot.set_id(self.next_bot_id);
+        self.next_bot_id += 1;
+        self.record(ReplayEvent::Spawn(ReplayBot::from_bot(&bot)));
+        self.tick_statistics.births += 1;
         self.bots.borrow_mut().push(bot);
     }
 
@@ -90,9 +351,11 @@ impl Simulator {
         let range_y = Range::new(self.field_size.1 * 0.1, self.field_size.1 * 0.9);
         let range_energy = Range::new(100, 500);
 
-        let mut rng = rand::thread_rng();
+        let rng = &mut self.rng;
 
-        self.food.borrow_mut().push(Food::new((range_x.ind_sample(&mut rng), range_y.ind_sample(&mut rng)), 10.0, range_energy.ind_sample(&mut rng)));
+        let food = Food::new((range_x.ind_sample(rng), range_y.ind_sample(rng)), 10.0, range_energy.ind_sample(rng));
+        self.record(ReplayEvent::Food(ReplayFood::from_food(&food)));
+        self.food.borrow_mut().push(food);
     }
 
     pub fn spawn_foods(&mut self, count: u32) {
@@ -103,91 +366,117 @@ impl Simulator {
 
     pub fn cycle(&mut self) {
         self.ticks += 1;
+        self.tick_statistics = TickStatistics::new(self.ticks);
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.begin_tick(self.ticks);
+        }
 
         self.check_food();
 
-        for bot in self.bots.borrow_mut().iter_mut() {
-            let env = self.create_environment(&bot);
-            bot.process(env);
+        {
+            let bot_circles: Vec<((f32, f32), f32)> = self.bots.borrow().iter().map(| bot | (bot.get_pos(), bot.get_size())).collect();
+            let food = self.food.borrow();
+            self.bot_grid.rebuild(bot_circles.iter().cloned());
+            self.food_grid.rebuild(food.iter().map(| food | (food.get_pos(), food.get_size())));
+
+            let world = World {
+                field_size: self.field_size,
+                boundaries: &self.boundaries,
+                food: &food,
+                food_grid: &self.food_grid,
+                bots: &bot_circles,
+                bot_grid: &self.bot_grid
+            };
+
+            for (index, bot) in self.bots.borrow_mut().iter_mut().enumerate() {
+                let env = create_environment(bot, index, &world, &mut self.rng);
+                bot.process(env, &*self.teacher, &mut self.rng);
+            }
         }
 
-        self.bots.borrow_mut().retain( | ref bot | bot.in_boundary(self.field_size) && bot.get_energy() > 0);
-        while self.bots.borrow().len() < self.min_bot_count as usize {
-            self.spawn_bot();
+        if let Some(ref mut recorder) = self.recorder {
+            for bot in self.bots.borrow().iter() {
+                recorder.record(&ReplayEvent::State(bot.get_id(), bot.get_pos(), bot.get_rotation(), bot.get_energy(), bot.get_actions().to_vec()));
+            }
         }
-    }
 
-    pub fn check_food(&mut self) {
-        let mut eat_counter = 0;
+        let field_size = self.field_size;
+        let (alive, mut dead): (Vec<Bot>, Vec<Bot>) = self.bots.borrow_mut()
+            .drain(..)
+            .partition( | bot | bot.in_boundary(field_size) && bot.get_energy() > 0);
+        *self.bots.borrow_mut() = alive;
 
         for bot in self.bots.borrow_mut().iter_mut() {
-            let mut eatable_index = None;
-            for (index, food) in self.food.borrow().iter().enumerate() {
-                if get_distance(bot.get_pos(), food.get_pos()) < food.get_size() {
-                    eatable_index = Some(index);
-                    break;
-                }
-            }
-            match eatable_index {
-                Some(food_index) => {
-                    let food = self.food.borrow_mut().swap_remove(food_index);
-                    bot.eat(food);
-                    eat_counter += 1;
-                }
-                None => {}
+            bot.reward(self.teacher.get_reward(Event::Tick));
+        }
+
+        for bot in dead.iter_mut() {
+            let cause = bot.get_death_cause(field_size).unwrap_or(DeathCause::Starved);
+            let event = match cause {
+                DeathCause::Starved => Event::Starved,
+                DeathCause::LeftField => Event::LeftField
+            };
+            bot.die(self.teacher.get_reward(event), &mut self.rng);
+            self.record(ReplayEvent::Death(bot.get_id(), cause));
+            self.tick_statistics.add_death(cause);
+        }
+
+        if let Some(ref mut evolution) = self.evolution {
+            for bot in dead.iter() {
+                evolution.record(bot);
             }
         }
 
-        if eat_counter > 0 {
-            self.spawn_foods(eat_counter);
+        while self.bots.borrow().len() < self.min_bot_count as usize {
+            self.spawn_bot();
         }
 
+        self.tick_statistics.measure(&self.bots.borrow(), &dead);
+        self.statistics.push(self.tick_statistics);
     }
 
-    fn create_environment(&self, bot: &Bot) -> Environment {
-        let mut environment = Environment::new(4);
+    //every bot eats at most one food per tick, a food is eaten by the first bot reaching it
+    pub fn check_food(&mut self) {
+        self.food_grid.rebuild(self.food.borrow().iter().map(| food | (food.get_pos(), food.get_size())));
 
-        let nearest_boundary = self.boundaries.iter()
-            .map( | boundary | bot.sees_line(*boundary))
-            .filter( | &e | e.is_some())
-            .map(| e | e.unwrap())
-            .fold((f32::MAX, 0.0), | min_val, e | match e.0 < min_val.0 { true => e, false => min_val});
+        let mut meals = Vec::new();
+        {
+            let food = self.food.borrow();
+            let mut eaten = vec![false; food.len()];
 
-        match nearest_boundary.0 < f32::MAX {
-            true => {
-                environment.set_input(0, nearest_boundary.0 as f64);
-                environment.set_input(1, nearest_boundary.1 as f64);
-            },
-            false => {
-                environment.set_input(0, 0.0);
-                environment.set_input(1, 0.0);
-            }
-        }
-
-        let mut nearest_food = (f32::MAX, 0.0);
-        for food in self.food.borrow().iter() {
-            let view_data = match bot.sees_point(food.get_pos()) {
-                Some(view_data) => view_data,
-                None => continue
-            };
+            for (bot_index, bot) in self.bots.borrow().iter().enumerate() {
+                let eatable_index = self.food_grid.query(bot.get_pos(), 0.0)
+                    .into_iter()
+                    .find(| &index | !eaten[index] && get_distance(bot.get_pos(), food[index].get_pos()) < food[index].get_size());
 
-            if view_data.0 < nearest_food.0 {
-                nearest_food = view_data;
+                if let Some(food_index) = eatable_index {
+                    eaten[food_index] = true;
+                    meals.push((bot_index, food_index));
+                }
             }
         }
 
-        match nearest_food.0 < f32::MAX {
-            true => {
-                environment.set_input(2, nearest_food.0 as f64);
-                environment.set_input(3, nearest_food.1 as f64);
-            },
-            false => {
-                environment.set_input(2, 0.0);
-                environment.set_input(3, 0.0);
+        if meals.is_empty() {
+            return;
+        }
+        self.tick_statistics.food_eaten += meals.len() as u32;
+
+        let mut food: Vec<Option<Food>> = self.food.borrow_mut().drain(..).map(Some).collect();
+        {
+            let mut bots = self.bots.borrow_mut();
+            for &(bot_index, food_index) in meals.iter() {
+                if let Some(food) = food[food_index].take() {
+                    if let Some(ref mut recorder) = self.recorder {
+                        recorder.record(&ReplayEvent::Eat(bots[bot_index].get_id(), food.get_pos()));
+                    }
+                    bots[bot_index].reward(self.teacher.get_reward(Event::Ate(food.get_energy())));
+                    bots[bot_index].eat(food);
+                }
             }
         }
+        *self.food.borrow_mut() = food.into_iter().flatten().collect();
 
-        environment
+        self.spawn_foods(meals.len() as u32);
     }
 
     /*
@@ -219,3 +508,47 @@ impl Simulator {
     }*/
 
 }
+
+#[test]
+fn test_same_seed_same_simulation() {
+    let mut sim_a = Simulator::with_seed((800, 600), 10, 42).unwrap();
+    let mut sim_b = Simulator::with_seed((800, 600), 10, 42).unwrap();
+
+    sim_a.fast_forward(200);
+    sim_b.fast_forward(200);
+
+    let pos_a: Vec<(f32, f32)> = sim_a.get_bots().borrow().iter().map(| bot | bot.get_pos()).collect();
+    let pos_b: Vec<(f32, f32)> = sim_b.get_bots().borrow().iter().map(| bot | bot.get_pos()).collect();
+    assert!(pos_a == pos_b);
+}
+
+#[test]
+fn test_qlearning_mode() {
+    use reinforcement::QLearningConfig;
+
+    let mut sim = Simulator::with_seed((800, 600), 0, 5).unwrap();
+    sim.set_learning_mode(LearningMode::QLearning(QLearningConfig::new().batch_size(4)));
+    sim.set_sensors(vec![Sensor::Rays(5), Sensor::NearestBot, Sensor::Energy]);
+    sim.spawn_bots(5);
+    sim.fast_forward(100);
+
+    for bot in sim.get_bots().borrow().iter() {
+        assert!(bot.get_learner().is_some());
+        assert!(bot.get_net().get_input_size() == 23);
+        assert!(bot.get_net().get_parameters().iter().all(| e | e.is_finite()));
+    }
+}
+
+#[test]
+fn test_find_bot_at() {
+    let sim = Simulator::with_seed((800, 600), 10, 9).unwrap();
+    let (id, pos) = {
+        let bots = sim.get_bots().borrow();
+        (bots[3].get_id(), bots[3].get_pos())
+    };
+
+    assert!(sim.find_bot_at(pos, 0.0) == Some(id));
+    assert!(sim.get_bot(id).unwrap().get_pos() == pos);
+    assert!(sim.find_bot_at((-1000.0, -1000.0), 5.0).is_none());
+    assert!(sim.get_bot(1000).is_none());
+}
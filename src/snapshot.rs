@@ -0,0 +1,296 @@
+//Snapshots of the complete world of a simulator.
+//
+//Text format (version 2), whitespace separated, one record per line:
+//  snapshot 2
+//  ticks <ticks>
+//  rng <x> <y> <z> <w>         the state of the random sequence
+//  field <width> <height>
+//  next_bot_id <id>            the id of the next spawned bot
+//  food <count>                followed by count records
+//    f <x> <y> <size> <energy>
+//  bots <count>                followed by count bots, each as
+//    bot <id> <x> <y> <rotation> <size> <speed> <energy> <age> <eaten energy> <velocity> <r> <g> <b>
+//    sensors <sensor> ...
+//    the network in the text format of serialization.rs, from "neuralnet" to "end"
+//    optimizer <group count>   followed by group count records
+//      group <value> ...
+//    learner <exploration>     the exploration of the q-learner, "-" for bots without one
+//  archive <count>             the evolution archive, followed by count records
+//    genome <fitness> <parameter> ...
+//  end
+//A loaded snapshot continues exactly like the saved simulator, saving doesn't change the simulator.
+//The configuration of the simulator (teacher, learning mode, evolution settings, sensors of new bots)
+//is not part of a snapshot, a snapshot is loaded into a simulator which provides it.
+//The replay memory of reinforcement learning is not stored, loaded bots start with an empty memory
+//but keep their exploration.
+
+use std::fs::File;
+use std::io::{ Read, Write, BufReader, BufWriter };
+use std::path::Path;
+
+use simulator::Simulator;
+use bot::Bot;
+use food::Food;
+use color::Color;
+use sensor::{ Sensor, get_input_size };
+use evolution::Genome;
+use neuralnet::NeuralNet;
+use serialization::{ io_error, next_record, parse_fields };
+use random::{ create_rng, get_state, from_state };
+
+pub const SNAPSHOT_VERSION: u32 = 2;
+const SNAPSHOT_HEADER: &str = "snapshot";
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values.iter().map(| e | e.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+impl Simulator {
+
+    pub fn save_snapshot(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(io_error)?;
+        self.write_snapshot(&mut BufWriter::new(file))
+    }
+
+    pub fn load_snapshot(&mut self, path: &Path) -> Result<(), String> {
+        let file = File::open(path).map_err(io_error)?;
+        self.read_snapshot(&mut BufReader::new(file))
+    }
+
+    pub fn write_snapshot<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        let field_size = self.get_field_size();
+        let mut text = format!("{} {}\n", SNAPSHOT_HEADER, SNAPSHOT_VERSION);
+        text += &format!("ticks {}\n", self.get_ticks());
+        text += &format!("rng {}\n", join(&get_state(self.get_rng())));
+        text += &format!("field {} {}\n", field_size.0, field_size.1);
+        text += &format!("next_bot_id {}\n", self.get_next_bot_id());
+
+        let food = self.get_foods().borrow();
+        text += &format!("food {}\n", food.len());
+        for food in food.iter() {
+            let pos = food.get_pos();
+            text += &format!("f {} {} {} {}\n", pos.0, pos.1, food.get_size(), food.get_energy());
+        }
+
+        let bots = self.get_bots().borrow();
+        text += &format!("bots {}\n", bots.len());
+        for bot in bots.iter() {
+            let pos = bot.get_pos();
+            let (r, g, b) = bot.get_color().get_rgb();
+            text += &format!("bot {} {} {} {} {} {} {} {} {} {} {} {} {}\n",
+                             bot.get_id(), pos.0, pos.1, bot.get_rotation(), bot.get_size(), bot.get_speed(),
+                             bot.get_energy(), bot.get_age(), bot.get_eaten_energy(), bot.get_velocity(),
+                             r, g, b);
+            text += &format!("sensors {}\n", bot.get_sensors().iter().map(| e | e.get_name()).collect::<Vec<_>>().join(" "));
+
+            let mut net_text = Vec::new();
+            bot.get_net().write_text(&mut net_text)?;
+            text += &String::from_utf8_lossy(&net_text);
+
+            let state = bot.get_net().get_optimizer().get_state();
+            text += &format!("optimizer {}\n", state.len());
+            for group in state.iter() {
+                text += &format!("group {}\n", join(group));
+            }
+            text += &format!("learner {}\n", bot.get_learner().map_or(String::from("-"), | learner | learner.get_exploration().to_string()));
+        }
+
+        let archive = self.get_evolution().map_or(&[][..], | evolution | evolution.get_archive());
+        text += &format!("archive {}\n", archive.len());
+        for genome in archive {
+            text += &format!("genome {} {}\n", genome.get_fitness(), join(genome.get_parameters()));
+        }
+        text += "end\n";
+
+        writer.write_all(text.as_bytes()).map_err(io_error)
+    }
+
+    //replaces the world of the simulator, the simulator stays unchanged if the snapshot is invalid
+    pub fn read_snapshot<R: Read>(&mut self, reader: &mut R) -> Result<(), String> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).map_err(io_error)?;
+
+        let mut lines = text.lines()
+            .filter(| line | !line.trim().is_empty())
+            .map(| line | line.split_whitespace().collect::<Vec<&str>>());
+        let lines = &mut lines;
+
+        let version = parse_fields::<u32>(&next_record(lines, SNAPSHOT_HEADER)?, 1, "snapshot version")?[0];
+        if version != SNAPSHOT_VERSION {
+            return Err(format!("Unsupported snapshot version {} (supported: {})", version, SNAPSHOT_VERSION));
+        }
+
+        let ticks = parse_fields::<u64>(&next_record(lines, "ticks")?, 1, "ticks")?[0];
+        let rng_state = parse_fields::<u32>(&next_record(lines, "rng")?, 4, "rng")?;
+        let rng = from_state([rng_state[0], rng_state[1], rng_state[2], rng_state[3]])?;
+        let field_size = parse_fields::<f32>(&next_record(lines, "field")?, 2, "field")?;
+        if (field_size[0], field_size[1]) != self.get_field_size() {
+            return Err(format!("The snapshot has a field of {} x {}, the simulator of {:?}", field_size[0], field_size[1], self.get_field_size()));
+        }
+        let next_bot_id = parse_fields::<u64>(&next_record(lines, "next_bot_id")?, 1, "next bot id")?[0];
+
+        let food_count = parse_fields::<usize>(&next_record(lines, "food")?, 1, "food count")?[0];
+        let mut food = Vec::with_capacity(food_count);
+        for _ in 0..food_count {
+            let fields = next_record(lines, "f")?;
+            if fields.len() != 4 {
+                return Err(format!("Expected 4 values for food, got {}", fields.len()));
+            }
+            let values = parse_fields::<f32>(&fields[0..3], 3, "food")?;
+            let energy = parse_fields::<u32>(&fields[3..4], 1, "food energy")?[0];
+            food.push(Food::new((values[0], values[1]), values[2], energy));
+        }
+
+        let bot_count = parse_fields::<usize>(&next_record(lines, "bots")?, 1, "bot count")?[0];
+        let mut bots = Vec::with_capacity(bot_count);
+        for _ in 0..bot_count {
+            bots.push(self.read_bot(lines)?);
+        }
+        if bots.iter().any(| bot | bot.get_id() >= next_bot_id) {
+            return Err(format!("The next bot id {} is already taken", next_bot_id));
+        }
+
+        let genome_count = parse_fields::<usize>(&next_record(lines, "archive")?, 1, "archive size")?[0];
+        let mut archive = Vec::with_capacity(genome_count);
+        for _ in 0..genome_count {
+            let fields = next_record(lines, "genome")?;
+            let values = parse_fields::<f64>(&fields, fields.len(), "genome")?;
+            if values.is_empty() {
+                return Err(String::from("Expected the fitness of a genome"));
+            }
+            archive.push(Genome::new(values[1..].to_vec(), values[0]));
+        }
+        next_record(lines, "end")?;
+
+        self.set_ticks(ticks);
+        self.set_rng(rng);
+        self.set_next_bot_id(next_bot_id);
+        *self.get_foods().borrow_mut() = food;
+        *self.get_bots().borrow_mut() = bots;
+        if let Some(evolution) = self.get_evolution_mut() {
+            evolution.set_archive(archive);
+        }
+        //the statistics belong to the replaced timeline
+        self.get_statistics_mut().clear();
+        Ok(())
+    }
+
+    fn read_bot<'a, I: Iterator<Item = Vec<&'a str>>>(&self, lines: &mut I) -> Result<Bot, String> {
+        let values = next_record(lines, "bot")?;
+        if values.len() != 13 {
+            return Err(format!("Expected 13 values for bot, got {}", values.len()));
+        }
+        let id = parse_fields::<u64>(&values[0..1], 1, "bot id")?[0];
+        let pos_rot_size_speed = parse_fields::<f32>(&values[1..6], 5, "bot")?;
+        let energy = parse_fields::<u32>(&values[6..7], 1, "bot energy")?[0];
+        let age_eaten = parse_fields::<u64>(&values[7..9], 2, "bot age")?;
+        let velocity = parse_fields::<f32>(&values[9..10], 1, "bot velocity")?[0];
+        let rgb = parse_fields::<u8>(&values[10..13], 3, "bot color")?;
+
+        let mut sensors = Vec::new();
+        for name in next_record(lines, "sensors")? {
+            sensors.push(Sensor::from_name(name)?);
+        }
+
+        let nn = NeuralNet::read_text_records(lines)?;
+        if get_input_size(&sensors) != nn.get_input_size() {
+            return Err(format!("The sensors of a bot give {} inputs, its network takes {}", get_input_size(&sensors), nn.get_input_size()));
+        }
+
+        let group_count = parse_fields::<usize>(&next_record(lines, "optimizer")?, 1, "optimizer groups")?[0];
+        let mut state = Vec::with_capacity(group_count);
+        for _ in 0..group_count {
+            let fields = next_record(lines, "group")?;
+            state.push(parse_fields::<f64>(&fields, fields.len(), "optimizer state")?);
+        }
+
+        let exploration = match next_record(lines, "learner")?[..] {
+            ["-"] => None,
+            ref fields => match parse_fields::<f64>(fields, 1, "exploration")?[0] {
+                exploration if (0.0..=1.0).contains(&exploration) => Some(exploration),
+                exploration => return Err(format!("Invalid exploration {}", exploration))
+            }
+        };
+
+        //the rng is only used for the color, which is restored below
+        let mut bot = Bot::with_net(nn, pos_rot_size_speed[3], pos_rot_size_speed[4], &mut create_rng(0));
+        bot.get_net_mut().get_optimizer_mut().set_state(state);
+        bot.set_sensors(sensors);
+        bot.set_pos((pos_rot_size_speed[0], pos_rot_size_speed[1]));
+        bot.set_rotation(pos_rot_size_speed[2]);
+        bot.set_energy(energy);
+        bot.set_age(age_eaten[0]);
+        bot.set_eaten_energy(age_eaten[1]);
+        bot.set_velocity(velocity);
+        bot.set_color(Color::from_rgb(rgb[0], rgb[1], rgb[2]));
+        bot.set_id(id);
+        self.attach_learner(&mut bot);
+        if let (Some(learner), Some(exploration)) = (bot.get_learner_mut(), exploration) {
+            learner.set_exploration(exploration);
+        }
+        Ok(bot)
+    }
+}
+
+#[test]
+fn test_snapshot_continues_identically() {
+    use evolution::{ Evolution, Selection };
+
+    let mut sim = Simulator::with_seed((800, 600), 10, 21).unwrap();
+    sim.set_evolution(Some(Evolution::new(Selection::Tournament(3), 0.1)));
+    sim.fast_forward(300);
+    let mut unsaved = Simulator::with_seed((800, 600), 10, 21).unwrap();
+    unsaved.set_evolution(Some(Evolution::new(Selection::Tournament(3), 0.1)));
+    unsaved.fast_forward(300);
+
+    let mut snapshot = Vec::new();
+    sim.write_snapshot(&mut snapshot).unwrap();
+
+    let mut restored = Simulator::with_seed((800, 600), 10, 0).unwrap();
+    restored.set_evolution(Some(Evolution::new(Selection::Tournament(3), 0.1)));
+    restored.fast_forward(10);
+    restored.read_snapshot(&mut &snapshot[..]).unwrap();
+    assert!(restored.get_ticks() == 300);
+    assert!(restored.get_statistics().is_empty());
+    //ids of bots which died before the snapshot are not handed out again
+    assert!(restored.get_next_bot_id() == sim.get_next_bot_id());
+
+    sim.fast_forward(300);
+    restored.fast_forward(300);
+    unsaved.fast_forward(300);
+
+    let state = | sim: &Simulator | sim.get_bots().borrow().iter()
+        .map(| bot | (bot.get_pos(), bot.get_energy(), bot.get_net().get_parameters()))
+        .collect::<Vec<_>>();
+    assert!(state(&sim) == state(&restored));
+    //saving leaves the run as it would have been without saving
+    assert!(state(&sim) == state(&unsaved));
+
+    //a snapshot of another field size is rejected and leaves the simulator untouched
+    let mut other = Simulator::with_seed((400, 300), 10, 0).unwrap();
+    assert!(other.read_snapshot(&mut &snapshot[..]).is_err());
+    assert!(other.get_ticks() == 0);
+}
+
+#[test]
+fn test_snapshot_keeps_exploration() {
+    use reinforcement::{ LearningMode, QLearningConfig };
+
+    let mut sim = Simulator::with_seed((800, 600), 0, 5).unwrap();
+    sim.set_learning_mode(LearningMode::QLearning(QLearningConfig::new().exploration(1.0, 0.05, 0.99)));
+    sim.set_bot_count(5);
+    sim.fast_forward(100);
+
+    let mut snapshot = Vec::new();
+    sim.write_snapshot(&mut snapshot).unwrap();
+
+    let mut restored = Simulator::with_seed((800, 600), 0, 0).unwrap();
+    restored.set_learning_mode(sim.get_learning_mode());
+    restored.read_snapshot(&mut &snapshot[..]).unwrap();
+
+    let exploration = | sim: &Simulator | sim.get_bots().borrow().iter()
+        .map(| bot | bot.get_learner().unwrap().get_exploration())
+        .collect::<Vec<_>>();
+    assert!(exploration(&sim).iter().all(| &e | e < 1.0));
+    assert!(exploration(&sim) == exploration(&restored));
+}
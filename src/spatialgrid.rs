@@ -0,0 +1,117 @@
+//A uniform grid over the field, which finds the objects near a point without looking at all objects.
+//Objects are stored by index and sorted into the cell of their center, positions outside the field
+//go to the nearest border cell. Queries widen their search by the largest inserted radius,
+//so every object whose circle can reach the query circle is returned.
+pub struct SpatialGrid {
+    cell_size: f32,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    max_radius: f32,
+    len: usize
+}
+
+impl SpatialGrid {
+
+    pub fn new(field_size: (f32, f32), cell_size: f32) -> SpatialGrid {
+        assert!(cell_size > 0.0);
+
+        let cols = ((field_size.0 / cell_size).ceil() as usize).max(1);
+        let rows = ((field_size.1 / cell_size).ceil() as usize).max(1);
+
+        SpatialGrid {
+            cell_size,
+            cols,
+            rows,
+            cells: vec![Vec::new(); cols * rows],
+            max_radius: 0.0,
+            len: 0
+        }
+    }
+
+    pub fn get_cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            cell.clear();
+        }
+        self.max_radius = 0.0;
+        self.len = 0;
+    }
+
+    pub fn insert(&mut self, index: usize, pos: (f32, f32), radius: f32) {
+        let (col, row) = self.get_cell(pos);
+        self.cells[row * self.cols + col].push(index);
+        self.max_radius = self.max_radius.max(radius);
+        self.len += 1;
+    }
+
+    //replaces the content with the circles, every circle is stored by its index in the iterator
+    pub fn rebuild<I: Iterator<Item = ((f32, f32), f32)>>(&mut self, circles: I) {
+        self.clear();
+        for (index, (pos, radius)) in circles.enumerate() {
+            self.insert(index, pos, radius);
+        }
+    }
+
+    //the indices of all objects that may touch the circle, in ascending order
+    pub fn query(&self, center: (f32, f32), radius: f32) -> Vec<usize> {
+        let reach = radius + self.max_radius;
+        let (min_col, min_row) = self.get_cell((center.0 - reach, center.1 - reach));
+        let (max_col, max_row) = self.get_cell((center.0 + reach, center.1 + reach));
+
+        let mut result = Vec::new();
+        for row in min_row..max_row + 1 {
+            for col in min_col..max_col + 1 {
+                result.extend_from_slice(&self.cells[row * self.cols + col]);
+            }
+        }
+        result.sort_unstable();
+        result
+    }
+
+    fn get_cell(&self, pos: (f32, f32)) -> (usize, usize) {
+        let col = (pos.0 / self.cell_size).floor().max(0.0) as usize;
+        let row = (pos.1 / self.cell_size).floor().max(0.0) as usize;
+        (col.min(self.cols - 1), row.min(self.rows - 1))
+    }
+}
+
+#[test]
+fn test_spatial_grid() {
+    use utility::get_distance;
+    use random::create_rng;
+    use rand::Rng;
+
+    let mut rng = create_rng(11);
+    let circles: Vec<((f32, f32), f32)> = (0..500)
+        .map(| _ | ((rng.gen_range(-20.0, 1020.0), rng.gen_range(-20.0, 520.0)), rng.gen_range(1.0, 15.0)))
+        .collect();
+
+    let mut grid = SpatialGrid::new((1000.0, 500.0), 64.0);
+    grid.rebuild(circles.iter().cloned());
+    assert!(grid.len() == 500);
+
+    //the grid finds at least every circle a linear search finds
+    for _ in 0..50 {
+        let center = (rng.gen_range(0.0, 1000.0), rng.gen_range(0.0, 500.0));
+        let radius = rng.gen_range(0.0, 150.0);
+        let found = grid.query(center, radius);
+
+        for (index, &(pos, size)) in circles.iter().enumerate() {
+            if get_distance(center, pos) <= radius + size {
+                assert!(found.binary_search(&index).is_ok());
+            }
+        }
+    }
+}
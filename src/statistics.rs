@@ -0,0 +1,290 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{ Write, BufWriter };
+use std::path::Path;
+
+use bot::{ Bot, DeathCause };
+use serialization::io_error;
+
+const CSV_HEADER: &str = "tick,population,births,deaths_starved,deaths_left_field,mean_energy,max_energy,food_eaten,mean_age,mean_error";
+
+//The metrics of one tick of a simulation.
+//Population and energy are measured at the end of the tick, after dead bots were replaced.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TickStatistics {
+    pub tick: u64,
+    pub population: u32,
+    pub births: u32,
+    pub deaths_starved: u32,
+    pub deaths_left_field: u32,
+    pub mean_energy: f64,
+    pub max_energy: u32,
+    pub food_eaten: u32,
+    pub mean_age: f64,
+    //mean training error of the bots which trained in this tick, None if none did
+    pub mean_error: Option<f64>
+}
+
+impl TickStatistics {
+
+    pub fn new(tick: u64) -> TickStatistics {
+        TickStatistics {
+            tick,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_deaths(&self) -> u32 {
+        self.deaths_starved + self.deaths_left_field
+    }
+
+    pub fn add_death(&mut self, cause: DeathCause) {
+        match cause {
+            DeathCause::Starved => self.deaths_starved += 1,
+            DeathCause::LeftField => self.deaths_left_field += 1
+        }
+    }
+
+    //fills in the metrics measured on the population, errors are taken from all bots which lived in this tick
+    pub fn measure(&mut self, bots: &[Bot], dead: &[Bot]) {
+        self.population = bots.len() as u32;
+        self.max_energy = bots.iter().map(| bot | bot.get_energy()).max().unwrap_or(0);
+        self.mean_energy = match bots.len() {
+            0 => 0.0,
+            count => bots.iter().fold(0u64, | sum, bot | sum + bot.get_energy() as u64) as f64 / count as f64
+        };
+
+        self.mean_age = match bots.len() {
+            0 => 0.0,
+            count => bots.iter().fold(0u64, | sum, bot | sum + bot.get_age()) as f64 / count as f64
+        };
+
+        let errors: Vec<f64> = bots.iter().chain(dead.iter()).filter_map(| bot | bot.get_training_error()).collect();
+        self.mean_error = match errors.len() {
+            0 => None,
+            count => Some(errors.iter().sum::<f64>() / count as f64)
+        };
+    }
+
+    fn get_csv_row(&self) -> String {
+        format!("{},{},{},{},{},{},{},{},{},{}\n",
+                self.tick, self.population, self.births, self.deaths_starved, self.deaths_left_field,
+                self.mean_energy, self.max_energy, self.food_eaten, self.mean_age,
+                self.mean_error.map_or(String::new(), | e | e.to_string()))
+    }
+
+    fn get_json_object(&self) -> String {
+        format!("{{\"tick\":{},\"population\":{},\"births\":{},\"deaths_starved\":{},\"deaths_left_field\":{},\"mean_energy\":{},\"max_energy\":{},\"food_eaten\":{},\"mean_age\":{},\"mean_error\":{}}}",
+                self.tick, self.population, self.births, self.deaths_starved, self.deaths_left_field,
+                json_number(self.mean_energy), self.max_energy, self.food_eaten, json_number(self.mean_age),
+                self.mean_error.map_or(String::from("null"), json_number))
+    }
+}
+
+//A time series which can be taken from the statistics.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Metric {
+    Population,
+    MeanEnergy,
+    //the food eaten in the last 100 ticks
+    FoodEatenPer100,
+    MeanAge,
+    //ticks without training count as 0
+    MeanError
+}
+
+impl Metric {
+
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            Metric::Population => "population",
+            Metric::MeanEnergy => "avg energy",
+            Metric::FoodEatenPer100 => "food eaten / 100 ticks",
+            Metric::MeanAge => "avg age",
+            Metric::MeanError => "avg error"
+        }
+    }
+}
+
+//json has no infinity or nan
+fn json_number(value: f64) -> String {
+    match value.is_finite() {
+        true => value.to_string(),
+        false => String::from("null")
+    }
+}
+
+//the number of ticks FoodEatenPer100 sums up
+pub const FOOD_WINDOW: usize = 100;
+
+//The time series of the tick statistics of a simulation.
+//With a history limit only the latest ticks are kept.
+pub struct Statistics {
+    history: VecDeque<TickStatistics>,
+    history_limit: Option<usize>
+}
+
+impl Default for Statistics {
+    fn default() -> Statistics {
+        Statistics::new()
+    }
+}
+
+impl Statistics {
+
+    pub fn new() -> Statistics {
+        Statistics {
+            history: VecDeque::new(),
+            history_limit: None
+        }
+    }
+
+    pub fn set_history_limit(&mut self, history_limit: Option<usize>) {
+        self.history_limit = history_limit;
+        self.truncate();
+    }
+
+    pub fn get_history_limit(&self) -> Option<usize> {
+        self.history_limit
+    }
+
+    pub fn push(&mut self, statistics: TickStatistics) {
+        self.history.push_back(statistics);
+        self.truncate();
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+
+    pub fn get_last(&self) -> Option<&TickStatistics> {
+        self.history.back()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TickStatistics> {
+        self.history.iter()
+    }
+
+    //the metric for the last length ticks, or for all ticks if there are fewer,
+    //sums over FOOD_WINDOW ticks need a history of length + FOOD_WINDOW ticks to be complete
+    pub fn get_series(&self, metric: Metric, length: usize) -> Vec<f64> {
+        let start = self.history.len().saturating_sub(length);
+        match metric {
+            Metric::FoodEatenPer100 => {
+                //a running sum, started early enough to cover the window of the first value
+                let first = start.saturating_sub(FOOD_WINDOW - 1);
+                let mut sum = 0;
+                let mut series = Vec::with_capacity(self.history.len() - start);
+                for index in first..self.history.len() {
+                    sum += self.history[index].food_eaten;
+                    if index >= first + FOOD_WINDOW {
+                        sum -= self.history[index - FOOD_WINDOW].food_eaten;
+                    }
+                    if index >= start {
+                        series.push(sum as f64);
+                    }
+                }
+                series
+            },
+            _ => self.history.iter().skip(start).map(| statistics | match metric {
+                Metric::Population => statistics.population as f64,
+                Metric::MeanEnergy => statistics.mean_energy,
+                Metric::MeanAge => statistics.mean_age,
+                Metric::MeanError => statistics.mean_error.unwrap_or(0.0),
+                Metric::FoodEatenPer100 => unreachable!()
+            }).collect()
+        }
+    }
+
+    //saves as json if the path ends with .json, otherwise as csv
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(io_error)?;
+        let mut writer = BufWriter::new(file);
+        match path.extension().is_some_and(| e | e == "json") {
+            true => self.write_json(&mut writer),
+            false => self.write_csv(&mut writer)
+        }?;
+        writer.flush().map_err(io_error)
+    }
+
+    //one row per tick, a missing error is an empty field
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        writer.write_all(CSV_HEADER.as_bytes()).map_err(io_error)?;
+        writer.write_all(b"\n").map_err(io_error)?;
+        for statistics in self.history.iter() {
+            writer.write_all(statistics.get_csv_row().as_bytes()).map_err(io_error)?;
+        }
+        Ok(())
+    }
+
+    //an array with one object per tick, a missing error is null
+    pub fn write_json<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        writer.write_all(b"[").map_err(io_error)?;
+        for (index, statistics) in self.history.iter().enumerate() {
+            let separator = match index {
+                0 => "\n  ",
+                _ => ",\n  "
+            };
+            writer.write_all(separator.as_bytes()).map_err(io_error)?;
+            writer.write_all(statistics.get_json_object().as_bytes()).map_err(io_error)?;
+        }
+        writer.write_all(b"\n]\n").map_err(io_error)
+    }
+
+    fn truncate(&mut self) {
+        if let Some(limit) = self.history_limit {
+            while self.history.len() > limit {
+                self.history.pop_front();
+            }
+        }
+    }
+}
+
+#[test]
+fn test_statistics_export() {
+    use simulator::Simulator;
+
+    let mut sim = Simulator::with_seed((800, 600), 10, 13).unwrap();
+    sim.fast_forward(400);
+
+    let statistics = sim.get_statistics();
+    assert!(statistics.len() == 400);
+    for tick in statistics.iter() {
+        assert!(tick.population >= 10);
+        assert!(tick.births == tick.get_deaths());
+    }
+    assert!(statistics.iter().any(| tick | tick.get_deaths() > 0));
+    assert!(statistics.iter().any(| tick | tick.food_eaten > 0));
+    assert!(statistics.iter().all(| tick | tick.mean_error.is_some()));
+
+    let mut csv = Vec::new();
+    statistics.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert!(csv.lines().count() == 401);
+    assert!(csv.lines().all(| line | line.split(',').count() == 10));
+
+    let mut json = Vec::new();
+    statistics.write_json(&mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.starts_with("[\n  {\"tick\":1,") && json.ends_with("}\n]\n"));
+    assert!(json.matches("\"tick\"").count() == 400);
+
+    let food = statistics.get_series(Metric::FoodEatenPer100, 50);
+    let eaten: Vec<u32> = statistics.iter().map(| tick | tick.food_eaten).collect();
+    assert!(food.len() == 50);
+    assert!(food[49] == eaten[300..].iter().sum::<u32>() as f64);
+    assert!(food[0] == eaten[251..351].iter().sum::<u32>() as f64);
+    assert!(statistics.get_series(Metric::Population, 1000).len() == 400);
+
+    sim.get_statistics_mut().set_history_limit(Some(100));
+    assert!(sim.get_statistics().len() == 100);
+    assert!(sim.get_statistics().iter().next().unwrap().tick == 301);
+}
@@ -0,0 +1,133 @@
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::{ Write, BufWriter };
+use std::path::Path;
+
+use color::Color;
+use renderer::Renderer;
+use serialization::io_error;
+
+const FONT_SIZE: f32 = 10.0;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+//Renders into an svg document, every primitive becomes one element.
+pub struct SvgRenderer {
+    size: (f32, f32),
+    body: String,
+    //the title of the next element
+    tooltip: Option<String>
+}
+
+impl SvgRenderer {
+
+    pub fn new(size: (f32, f32)) -> SvgRenderer {
+        SvgRenderer {
+            size,
+            body: String::new(),
+            tooltip: None
+        }
+    }
+
+    fn add_element(&mut self, tag: &str, attributes: String, content: &str) {
+        let content = match self.tooltip.take() {
+            Some(tooltip) => format!("<title>{}</title>{}", escape(&tooltip), content),
+            None => String::from(content)
+        };
+        self.body += &match content.is_empty() {
+            true => format!("<{} {}/>\n", tag, attributes),
+            false => format!("<{} {}>{}</{}>\n", tag, attributes, content, tag)
+        };
+    }
+
+    pub fn get_document(&self) -> String {
+        format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n{}</svg>\n",
+                self.size.0, self.size.1, self.size.0, self.size.1, self.body)
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        writer.write_all(self.get_document().as_bytes()).map_err(io_error)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(io_error)?;
+        let mut writer = BufWriter::new(file);
+        self.write(&mut writer)?;
+        writer.flush().map_err(io_error)
+    }
+}
+
+impl Renderer for SvgRenderer {
+
+    fn get_size(&self) -> (f32, f32) {
+        self.size
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.body = format!("<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", self.size.0, self.size.1, color.get_hex());
+    }
+
+    fn draw_line(&mut self, from: (f32, f32), to: (f32, f32), color: Color, thickness: f32) {
+        self.add_element("line", format!("x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\"",
+                                         from.0, from.1, to.0, to.1, color.get_hex(), thickness), "");
+    }
+
+    fn draw_circle(&mut self, center: (f32, f32), radius: f32, color: Color, thickness: f32) {
+        self.add_element("circle", format!("cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"",
+                                           center.0, center.1, radius, color.get_hex(), thickness), "");
+    }
+
+    fn draw_filled_circle(&mut self, center: (f32, f32), radius: f32, color: Color) {
+        self.add_element("circle", format!("cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"", center.0, center.1, radius, color.get_hex()), "");
+    }
+
+    fn draw_pieslice(&mut self, center: (f32, f32), radius: f32, start_theta: f32, delta_theta: f32, color: Color, thickness: f32) {
+        if delta_theta.abs() >= 2.0 * PI {
+            self.draw_circle(center, radius, color, thickness);
+            return;
+        }
+
+        let point = | theta: f32 | (center.0 + radius * theta.cos(), center.1 + radius * theta.sin());
+        let start = point(start_theta);
+        let end = point(start_theta + delta_theta);
+        self.add_element("path", format!("d=\"M {} {} L {} {} A {} {} 0 {} {} {} {} Z\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"",
+                                         center.0, center.1, start.0, start.1, radius, radius,
+                                         (delta_theta.abs() > PI) as u8, (delta_theta > 0.0) as u8, end.0, end.1,
+                                         color.get_hex(), thickness), "");
+    }
+
+    fn draw_filled_rectangle(&mut self, from: (f32, f32), to: (f32, f32), color: Color) {
+        self.add_element("rect", format!("x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"",
+                                         from.0.min(to.0), from.1.min(to.1), (to.0 - from.0).abs(), (to.1 - from.1).abs(), color.get_hex()), "");
+    }
+
+    fn draw_text(&mut self, pos: (f32, f32), color: Color, text: &str) {
+        self.add_element("text", format!("x=\"{}\" y=\"{}\" fill=\"{}\" font-family=\"monospace\" font-size=\"{}\"",
+                                         pos.0, pos.1 + FONT_SIZE, color.get_hex(), FONT_SIZE), &escape(text));
+    }
+
+    fn set_tooltip(&mut self, text: &str) {
+        self.tooltip = Some(String::from(text));
+    }
+}
+
+#[test]
+fn test_svg_renderer() {
+    use simulator::Simulator;
+    use renderer::render_simulation;
+
+    let sim = Simulator::with_seed((800, 600), 10, 17).unwrap();
+    let mut renderer = SvgRenderer::new((400.0, 300.0));
+    render_simulation(&mut renderer, &sim, (0.0, 0.0), (0.5, 0.5));
+    renderer.draw_text((5.0, 5.0), Color::from_rgb(0xFF, 0xFF, 0xFF), "ticks < 1 & more");
+
+    let svg = renderer.get_document();
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+    //one circle per food and per bot, one view cone per bot and four borders
+    assert!(svg.matches("<circle").count() == 50 + 10);
+    assert!(svg.matches("<path").count() == 10);
+    assert!(svg.matches("<line").count() == 4);
+    assert!(svg.contains(">ticks &lt; 1 &amp; more</text>"));
+}
@@ -0,0 +1,130 @@
+use std::f64::consts::PI;
+
+use environment::Environment;
+use sensor::Sensor;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    //a bot survived a tick
+    Tick,
+    //a bot ate food with the given energy
+    Ate(u32),
+    LeftField,
+    Starved
+}
+
+//Decides what a bot learns: training targets for supervised learning and rewards for reinforcement learning.
+pub trait Teacher {
+    //the output the bot should have produced for its environment, None skips training for this tick
+    fn get_expected_output(&self, _environment: &Environment, _output: &[f64]) -> Option<Vec<f64>> {
+        None
+    }
+
+    fn get_reward(&self, _event: Event) -> f64 {
+        0.0
+    }
+}
+
+//The hand-written rules for the default environment layout:
+//turn away from walls, turn towards food and otherwise move forward.
+//The outputs are the actions of a bot: turning clockwise, turning counterclockwise and moving forward.
+pub struct RuleTeacher {
+    food_reward: f64,
+    boundary_penalty: f64,
+    starvation_penalty: f64
+}
+
+impl Default for RuleTeacher {
+    fn default() -> RuleTeacher {
+        RuleTeacher::new()
+    }
+}
+
+impl RuleTeacher {
+
+    pub fn new() -> RuleTeacher {
+        RuleTeacher {
+            food_reward: 0.01,
+            boundary_penalty: -10.0,
+            starvation_penalty: -5.0
+        }
+    }
+
+    //reward per unit of eaten food energy
+    pub fn food_reward(mut self, food_reward: f64) -> Self {
+        self.food_reward = food_reward;
+        self
+    }
+
+    pub fn boundary_penalty(mut self, boundary_penalty: f64) -> Self {
+        self.boundary_penalty = boundary_penalty;
+        self
+    }
+
+    pub fn starvation_penalty(mut self, starvation_penalty: f64) -> Self {
+        self.starvation_penalty = starvation_penalty;
+        self
+    }
+}
+
+impl Teacher for RuleTeacher {
+    //needs the nearest wall and nearest food sensors, without them there is nothing to teach
+    fn get_expected_output(&self, environment: &Environment, output: &[f64]) -> Option<Vec<f64>> {
+        let wall = environment.get_sensor_input(Sensor::NearestWall)?;
+        let food = environment.get_sensor_input(Sensor::NearestFood)?;
+        let input = [wall[0], wall[1], food[0], food[1]];
+        let mut target_output = vec![0.0; output.len()];
+
+        if input[0] > 0.0 {
+            match input[1] {
+                e if e > 0.0 && e < PI => target_output[1] = 1.0,
+                e if e < 0.0 && e > -PI => target_output[0] = 1.0,
+                _ => {}
+            }
+            target_output[2] = 0.0;
+        }
+        else if input[2] > 0.0 {
+            match input[3] {
+                e if e > 0.0 => target_output[0] = e,
+                e if e < 0.0 => target_output[1] = -e,
+                _ => {}
+            }
+            target_output[2] = 0.5;
+        }
+        else{
+            target_output[0] = 0.0; //output[0];
+            target_output[1] = 0.0; //output[1];
+            target_output[2] = 1.0;
+        }
+
+        Some(target_output)
+    }
+
+    fn get_reward(&self, event: Event) -> f64 {
+        match event {
+            Event::Tick => 0.0,
+            Event::Ate(energy) => self.food_reward * energy as f64,
+            Event::LeftField => self.boundary_penalty,
+            Event::Starved => self.starvation_penalty
+        }
+    }
+}
+
+#[test]
+fn test_rule_teacher() {
+    let teacher = RuleTeacher::new().food_reward(0.5);
+
+    //a wall 0.5 rad clockwise, i.e. to the right as y points down:
+    //turn counterclockwise (output 1) away from it and stop (output 2)
+    let mut environment = Environment::new(&Sensor::default_suite());
+    environment.set_input(0, 30.0);
+    environment.set_input(1, 0.5);
+    assert!(teacher.get_expected_output(&environment, &[0.0; 3]) == Some(vec![0.0, 1.0, 0.0]));
+
+    //the rules need both the wall and the food sensor
+    let environment = Environment::new(&[Sensor::NearestFood, Sensor::Energy]);
+    assert!(teacher.get_expected_output(&environment, &[0.0; 3]).is_none());
+
+    assert!(teacher.get_reward(Event::Ate(100)) == 50.0);
+    assert!(teacher.get_reward(Event::LeftField) < 0.0);
+}
@@ -1,9 +1,38 @@
+//Dataset files hold one set per line, the inputs and the targets separated by "|":
+//  0 1 | 1
+//Values are separated by whitespace, empty lines and lines starting with "#" are ignored.
+
+use std::fs::File;
+use std::io::{ BufRead, BufReader };
+use std::path::Path;
 use std::slice::Iter;
 
+use serialization::io_error;
+
 pub struct Trainingset {
     sets: Vec<(Vec<f64>, Vec<f64>)>
 }
 
+impl Default for Trainingset {
+    fn default() -> Trainingset {
+        Trainingset::new()
+    }
+}
+
+fn parse_values(text: &str, line_number: usize) -> Result<Vec<f64>, String> {
+    let mut values = Vec::new();
+    for field in text.split_whitespace() {
+        match field.parse::<f64>() {
+            Ok(value) => values.push(value),
+            Err(_) => return Err(format!("Invalid value \"{}\" in line {}", field, line_number))
+        }
+    }
+    match values.is_empty() {
+        true => Err(format!("Missing values in line {}", line_number)),
+        false => Ok(values)
+    }
+}
+
 impl Trainingset {
 
     pub fn new() -> Trainingset {
@@ -12,6 +41,42 @@ impl Trainingset {
         }
     }
 
+    pub fn load(path: &Path) -> Result<Trainingset, String> {
+        let file = File::open(path).map_err(io_error)?;
+        Trainingset::read(&mut BufReader::new(file))
+    }
+
+    //fails unless every set has the input and target count of the first one
+    pub fn read<R: BufRead>(reader: &mut R) -> Result<Trainingset, String> {
+        let mut trainingset = Trainingset::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(io_error)?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line_number = index + 1;
+            let mut parts = line.split('|');
+            let (input, output) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(input), Some(output), None) => (parse_values(input, line_number)?, parse_values(output, line_number)?),
+                _ => return Err(format!("Expected inputs and targets separated by \"|\" in line {}", line_number))
+            };
+
+            if trainingset.get_set_count() > 0 && (input.len(), output.len()) != (trainingset.get_input_size(), trainingset.get_output_size()) {
+                return Err(format!("Expected {} inputs and {} targets in line {}, got {} and {}",
+                                   trainingset.get_input_size(), trainingset.get_output_size(), line_number, input.len(), output.len()));
+            }
+            trainingset.add_set(input, output);
+        }
+
+        match trainingset.get_set_count() {
+            0 => Err(String::from("The dataset contains no sets")),
+            _ => Ok(trainingset)
+        }
+    }
+
     pub fn add_set(&mut self, input: Vec<f64>, output: Vec<f64>) {
         self.sets.push((input, output))
     }
@@ -20,8 +85,32 @@ impl Trainingset {
         self.sets.len()
     }
 
-    pub fn iter(&self) -> Iter<(Vec<f64>, Vec<f64>)> {
+    //the input count of the first set, 0 if there are no sets
+    pub fn get_input_size(&self) -> usize {
+        self.sets.first().map_or(0, | set | set.0.len())
+    }
+
+    //the target count of the first set, 0 if there are no sets
+    pub fn get_output_size(&self) -> usize {
+        self.sets.first().map_or(0, | set | set.1.len())
+    }
+
+    pub fn iter(&self) -> Iter<'_, (Vec<f64>, Vec<f64>)> {
         self.sets.iter()
     }
 
 }
+
+#[test]
+fn test_read_dataset() {
+    let text = "# xor\n0 0 | 0\n0 1 | 1\n\n1 0 | 1\n1 1 | 0\n";
+    let trainingset = Trainingset::read(&mut text.as_bytes()).unwrap();
+    assert!(trainingset.get_set_count() == 4);
+    assert!((trainingset.get_input_size(), trainingset.get_output_size()) == (2, 1));
+    assert!(trainingset.iter().nth(1).unwrap() == &(vec![0.0, 1.0], vec![1.0]));
+
+    assert!(Trainingset::read(&mut "0 0 | 0\n0 1 | 1 0\n".as_bytes()).is_err());
+    assert!(Trainingset::read(&mut "0 0 0\n".as_bytes()).is_err());
+    assert!(Trainingset::read(&mut "0 x | 1\n".as_bytes()).is_err());
+    assert!(Trainingset::read(&mut "# nothing\n".as_bytes()).is_err());
+}
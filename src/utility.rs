@@ -15,7 +15,7 @@ pub trait Vector2D
     fn normalize(self) -> Self;
     fn length(&self) -> f32;
     fn get_angle_diff(self, other: Self) -> f32;
-    fn is_clockwise(self, other: Self) -> bool;
+    fn is_clockwise(&self, other: Self) -> bool;
     fn dot(self, other: Self) -> f32;
     fn add(self, other: Self) -> Self;
     fn sub(self, other: Self) -> Self;
@@ -45,8 +45,8 @@ impl Vector2D for (f32, f32) {
         angle
     }
 
-    fn is_clockwise(self, other: Self) -> bool {
-        !(self.1 * other.0 > self.0 * other.1)
+    fn is_clockwise(&self, other: Self) -> bool {
+        self.1 * other.0 <= self.0 * other.1
     }
 
     fn dot(self, other: Self) -> f32 {
@@ -151,7 +151,7 @@ fn test_circle_intersects_line() {
 
     let points = circle_intersects_line(circle_pos, radius, ((x, y), angle));
 
-    assert!(points[0] != None && points[1] != None);
+    assert!(points[0].is_some() && points[1].is_some());
 
     let (x_1, y_1) = points[0].unwrap();
     let (x_2, y_2) = points[1].unwrap();
@@ -161,6 +161,7 @@ fn test_circle_intersects_line() {
 
 }
 
+#[cfg(test)]
 fn round(num: f32, precision: i32) -> f32 {
     let mult = 10.0f32.powi(precision);
     (num * mult).round() / mult
@@ -10,7 +10,6 @@ pub struct Vector2<T> {
 }
 
 pub struct Vector3<T> {
-    size_a: usize,
     size_b: usize,
     size_c: usize,
     data: Vec<T>
@@ -35,14 +34,30 @@ impl<T> Vector2<T>
         &self.data[row * self.size_col + col]
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         self.data.iter_mut()
     }
 
-    pub fn iter(&mut self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         self.data.iter()
     }
 
+    pub fn get_row_count(&self) -> usize {
+        self.size_row
+    }
+
+    pub fn get_col_count(&self) -> usize {
+        self.size_col
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
     pub fn get_row(&self, row: usize) -> &[T] {
         &self.data[row * self.size_col..(row + 1) * self.size_col]
     }
@@ -60,7 +75,6 @@ impl<T> Vector3<T>
 
     pub fn new(init_val: T, dim_a: usize, dim_b: usize, dim_c: usize) -> Vector3<T> {
         Vector3{
-            size_a: dim_a,
             size_b: dim_b,
             size_c: dim_c,
             data: vec![init_val; dim_a * dim_b * dim_c]
@@ -75,11 +89,11 @@ impl<T> Vector3<T>
         &self.data[pos_a * self.size_b * self.size_c + pos_b * self.size_c + pos_c]
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         self.data.iter_mut()
     }
 
-    pub fn iter(&mut self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         self.data.iter()
     }
 
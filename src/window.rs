@@ -1,12 +1,22 @@
 use std::option::Option;
 use std::f32;
+use std::path::PathBuf;
 //use std::time::{ Duration, Instant };
 
-use allegro::{ KeyCode, KeyDown, MouseButtonDown, MouseAxes, TimerTick, Timer, Bitmap, Flag, Color };
+use allegro::{ KeyCode, KeyDown, MouseButtonDown, MouseAxes, TimerTick, Timer, Bitmap, Flag };
 use allegro_font::{ FontDrawing, FontAlign };
 
-use allegrodata::{ AllegroData, Drawable };
+use allegrodata::{ AllegroData, AllegroRenderer };
+use renderer::{ Renderer, Drawable, render_simulation, draw_field_border, get_background_color };
+use color::Color;
 use simulator::Simulator;
+use replay::Replay;
+use graphpanel::GraphPanel;
+use statistics::FOOD_WINDOW;
+use inspector::Inspector;
+use netdiagram::NetDiagram;
+
+const NET_DIAGRAM_SIZE: (f32, f32) = (800.0, 500.0);
 
 pub struct Window {
     allegro_data: AllegroData,
@@ -20,6 +30,13 @@ pub struct Window {
     tickrate: i32,
     timer_bot_update: Timer,
     timer_redraw: Timer,
+    snapshot_path: PathBuf,
+    //with a replay the window plays it back instead of running the simulator
+    replay: Option<Replay>,
+    graph_panel: GraphPanel,
+    inspector: Inspector,
+    //the id of the bot shown in the inspector
+    selected_bot: Option<u64>,
 }
 
 pub struct WindowBuilder {
@@ -28,7 +45,9 @@ pub struct WindowBuilder {
     frame_size: (f32, f32),
     tickrate: i32,
     redraw_rate: u32,
-    simulator: Option<Simulator>
+    simulator: Option<Simulator>,
+    snapshot_path: PathBuf,
+    replay: Option<Replay>
 }
 
 impl WindowBuilder {
@@ -38,12 +57,14 @@ impl WindowBuilder {
         assert!(screen_size.1 > 0);
 
         WindowBuilder {
-            screen_size: screen_size,
+            screen_size,
             frame_pos: (0.0, 0.0),
             frame_size: (screen_size.0 as f32, screen_size.1 as f32),
             tickrate: 30,
             redraw_rate: 30,
-            simulator: None
+            simulator: None,
+            snapshot_path: PathBuf::from("snapshot.txt"),
+            replay: None
         }
     }
 
@@ -72,27 +93,36 @@ impl WindowBuilder {
         self
     }
 
+    //the file F5 saves the world to and F9 restores it from
+    pub fn snapshot_path(mut self, snapshot_path: PathBuf) -> Self {
+        self.snapshot_path = snapshot_path;
+        self
+    }
+
+    //plays the replay back, the timer advances it by one recorded tick
+    pub fn replay(mut self, replay: Replay) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+
     pub fn finish(mut self) -> Result<Window, String> {
 
-        let allegro_data = match AllegroData::new(self.screen_size.0 as i32, self.screen_size.1 as i32) {
-            Ok(e) => e,
-            Err(e) => return Err(e)
-        };
+        let allegro_data = AllegroData::new(self.screen_size.0, self.screen_size.1)?;
 
         let field_bmp = match Bitmap::new(allegro_data.get_core(), self.frame_size.0 as i32, self.frame_size.1 as i32) {
             Ok(e) => e,
             Err(_) => return Err(String::from("Could create field bitmap"))
         };
 
-        let timer_tick = match allegro_data.create_timer(1.0 / self.tickrate as f64) {
-            Ok(e) => e,
-            Err(e) => return Err(e)
-        };
+        let timer_tick = allegro_data.create_timer(1.0 / self.tickrate as f64)?;
 
-        let timer_redraw = match allegro_data.create_timer(1.0 / self.redraw_rate as f64) {
-            Ok(e) => e,
-            Err(e) => return Err(e)
-        };
+        let timer_redraw = allegro_data.create_timer(1.0 / self.redraw_rate as f64)?;
+
+        //an empty simulator provides the field of the replay
+        if let (None, Some(replay)) = (&self.simulator, &self.replay) {
+            let field_size = replay.get_field_size();
+            self.simulator = Some(Simulator::new((field_size.0 as i32, field_size.1 as i32), 0)?);
+        }
 
         if self.simulator.is_none() {
             self.simulator = match Simulator::new((self.frame_size.0 as i32, self.frame_size.1 as i32), 20) {
@@ -101,21 +131,39 @@ impl WindowBuilder {
             };
         }
 
-        let simulator = self.simulator.unwrap();
+        let mut simulator = self.simulator.unwrap();
         let field_size = simulator.get_field_size();
 
+        //in the upper right corner of the field frame
+        let graph_panel_size = (300.0_f32.min(self.frame_size.0), 400.0_f32.min(self.frame_size.1));
+        let graph_panel = GraphPanel::new((self.frame_pos.0 + self.frame_size.0 - graph_panel_size.0, self.frame_pos.1), graph_panel_size);
+
+        //the window only shows the ticks of the graphs, so the statistics don't grow with the runtime
+        if simulator.get_statistics().get_history_limit().is_none() {
+            simulator.get_statistics_mut().set_history_limit(Some(graph_panel.get_length() + FOOD_WINDOW));
+        }
+
+        //along the left side of the field frame
+        let inspector_size = (720.0_f32.min(self.frame_size.0), self.frame_size.1);
+        let inspector = Inspector::new(self.frame_pos, inspector_size, 320.0_f32.min(inspector_size.0));
+
         let window = Window {
-            allegro_data: allegro_data,
-            simulator: simulator,
+            allegro_data,
+            simulator,
             frame_pos: self.frame_pos,
             frame_size: self.frame_size,
             camera_pos: (0.0, 0.0),
             camera_view_size: field_size,
             scale: (self.frame_size.0 / field_size.0, self.frame_size.1 / field_size.1),
-            field_bmp: field_bmp,
+            field_bmp,
             tickrate: self.tickrate,
             timer_bot_update: timer_tick,
-            timer_redraw: timer_redraw
+            timer_redraw,
+            snapshot_path: self.snapshot_path,
+            replay: self.replay,
+            graph_panel,
+            inspector,
+            selected_bot: None
         };
 
         Ok(window)
@@ -129,7 +177,9 @@ impl Window {
         let mut redraw = false;
         static TICKRATE_MOD_STEP: i32 = 10;
         static CAMERA_MOVE_STEP: f32 = 100.0;
-        static CAMERA_ZOOM_FACTOR: f32 = 1.5;
+        static REPLAY_LARGE_STEP: i64 = 100;
+        //in screen pixels, so small bots stay clickable when zoomed out
+        static BOT_CLICK_MARGIN: f32 = 4.0;
 
         self.timer_bot_update.start();
         self.timer_redraw.start();
@@ -143,6 +193,28 @@ impl Window {
 
             match self.allegro_data.get_event_queue().wait_for_event() {
 
+                KeyDown{ keycode: k, .. } if self.replay.is_some() => {
+                    let replay = self.replay.as_mut().unwrap();
+                    match k {
+                        KeyCode::Escape => break 'exit,
+                        KeyCode::Space => self.toggle_timers(),
+                        KeyCode::Comma => replay.step(-1),
+                        KeyCode::Fullstop => replay.step(1),
+                        KeyCode::PgUp => replay.step(-REPLAY_LARGE_STEP),
+                        KeyCode::PgDn => replay.step(REPLAY_LARGE_STEP),
+                        KeyCode::Home => { let tick = replay.get_first_tick(); replay.seek(tick) },
+                        KeyCode::End => { let tick = replay.get_last_tick(); replay.seek(tick) },
+                        KeyCode::I => self.mod_speed(TICKRATE_MOD_STEP),
+                        KeyCode::O => self.mod_speed(-TICKRATE_MOD_STEP),
+                        KeyCode::Left => self.move_camera((-CAMERA_MOVE_STEP, 0.0)),
+                        KeyCode::Right => self.move_camera((CAMERA_MOVE_STEP, 0.0)),
+                        KeyCode::Up => self.move_camera((0.0, -CAMERA_MOVE_STEP)),
+                        KeyCode::Down => self.move_camera((0.0, CAMERA_MOVE_STEP)),
+                        _ => {}
+                    }
+                    redraw = true;
+                },
+
                 KeyDown{ keycode: k, .. } => match k {
                     KeyCode::Escape => break 'exit,
                     KeyCode::F => {
@@ -151,6 +223,24 @@ impl Window {
                         self.timer_bot_update.start();
                     },
                     KeyCode::Space => self.toggle_timers(),
+                    KeyCode::N => self.export_net_diagram(),
+                    KeyCode::G => {
+                        self.graph_panel.toggle();
+                        redraw = true;
+                    },
+                    KeyCode::F5 => match self.simulator.save_snapshot(&self.snapshot_path) {
+                        Ok(()) => println!("saved snapshot to {}", self.snapshot_path.display()),
+                        Err(e) => println!("ERROR: could not save snapshot: {}", e)
+                    },
+                    //the recording can't follow the jump to another timeline
+                    KeyCode::F9 if self.simulator.is_recording() => println!("ERROR: can't load a snapshot while recording"),
+                    KeyCode::F9 => match self.simulator.load_snapshot(&self.snapshot_path) {
+                        Ok(()) => {
+                            println!("loaded snapshot from {}", self.snapshot_path.display());
+                            redraw = true;
+                        },
+                        Err(e) => println!("ERROR: could not load snapshot: {}", e)
+                    },
                     KeyCode::I => self.mod_speed(TICKRATE_MOD_STEP),
                     KeyCode::O => self.mod_speed(-TICKRATE_MOD_STEP),
                     KeyCode::Left => self.move_camera((-CAMERA_MOVE_STEP, 0.0)),
@@ -165,7 +255,15 @@ impl Window {
                     //but here to not catch all left clicks (even those outside the field frame)
                     1 if self.point_in_frame((pos_x as f32, pos_y as f32))  => {
                         if let Some(frame_pos) = self.mouse_pos_to_frame_pos((pos_x as f32, pos_y as f32)) {
-                            self.move_camera_to_mouse_click(frame_pos);
+                            //a click on a bot selects it, anywhere else it deselects and moves the camera
+                            self.selected_bot = match self.replay {
+                                Some(_) => None,
+                                None => self.simulator.find_bot_at(self.frame_pos_to_field_pos(frame_pos), BOT_CLICK_MARGIN / self.scale.0)
+                            };
+                            if self.selected_bot.is_none() {
+                                self.move_camera_to_mouse_click(frame_pos);
+                            }
+                            redraw = true;
                         }
 
                         },
@@ -183,7 +281,7 @@ impl Window {
                     }
                 },
 
-                ///Unrough version, without camera movement
+                //Unrough version, without camera movement
                 /*MouseAxes{ dz: wheel_rotation, .. } => match wheel_rotation {
                     1 => self.zoom_camera(0.9),
                     -1 => self.zoom_camera(1.1),
@@ -193,7 +291,10 @@ impl Window {
                 TimerTick{source: src, ..} => {
                     match src == self.timer_redraw.get_event_source().get_event_source() {
                         true => redraw = true,
-                        false => self.simulator.cycle()
+                        false => match self.replay {
+                            Some(ref mut replay) => replay.step(1),
+                            None => self.simulator.cycle()
+                        }
                     }
                 },
                 _ => {}
@@ -290,6 +391,28 @@ impl Window {
         point.1 < self.frame_pos.1 + self.frame_size.1
     }
 
+    //saves the network of the selected bot as svg, next to the snapshot
+    fn export_net_diagram(&self) {
+        let id = match self.selected_bot {
+            Some(id) => id,
+            None => return
+        };
+        let bot = match self.simulator.get_bot(id) {
+            Some(bot) => bot,
+            None => return
+        };
+
+        let path = self.snapshot_path.with_file_name(format!("net_{}.svg", id));
+        match NetDiagram::new(bot.get_net(), NET_DIAGRAM_SIZE).save_svg(&path) {
+            Ok(()) => println!("saved network diagram to {}", path.display()),
+            Err(e) => println!("ERROR: could not save network diagram: {}", e)
+        }
+    }
+
+    fn frame_pos_to_field_pos(&self, frame_pos: (f32, f32)) -> (f32, f32) {
+        (self.camera_pos.0 + frame_pos.0 / self.scale.0, self.camera_pos.1 + frame_pos.1 / self.scale.1)
+    }
+
     fn mouse_pos_to_frame_pos(&self, click_pos: (f32, f32)) -> Option<(f32, f32)> {
         match self.point_in_frame(click_pos) {
             true => Some((click_pos.0 - self.frame_pos.0, click_pos.1 - self.frame_pos.1)),
@@ -307,53 +430,79 @@ impl Window {
         /*static mut c: u32 = 0;
         let now = Instant::now();*/
 
-        const BORDER_THICKNESS: f32 = 2.0;
-        const BORDER_THICKNESS_HALF: f32 = BORDER_THICKNESS / 2.0;
-
         let core = self.allegro_data.get_core();
-        let field_size = self.simulator.get_field_size();
 
         core.clear_to_color(self.allegro_data.get_black());
 
 
         core.set_target_bitmap(&self.field_bmp);
 
-        core.clear_to_color(Color::from_rgb(22, 22, 22));
+        let mut renderer = AllegroRenderer::new(&self.allegro_data, self.frame_size);
 
-        for food in self.simulator.get_foods().borrow().iter() {
-            food.draw(&self.allegro_data, self.camera_pos, self.scale);
-        }
+        match self.replay {
+            Some(ref replay) => {
+                renderer.clear(get_background_color());
+
+                for food in replay.get_state().get_food() {
+                    food.draw(&mut renderer, self.camera_pos, self.scale);
+                }
 
-        for bot in self.simulator.get_bots().borrow().iter() {
-            if self.point_in_view(bot.get_pos()){
-                bot.draw(&self.allegro_data, self.camera_pos, self.scale);
+                for bot in replay.get_state().get_bots() {
+                    if self.point_in_view(bot.pos) {
+                        bot.draw(&mut renderer, self.camera_pos, self.scale);
+                    }
+                }
+
+                draw_field_border(&mut renderer, replay.get_field_size(), self.camera_pos, self.scale);
+            },
+            None => {
+                render_simulation(&mut renderer, &self.simulator, self.camera_pos, self.scale);
+
+                if let Some(bot) = self.selected_bot.and_then(| id | self.simulator.get_bot(id)) {
+                    let pos = bot.get_pos();
+                    renderer.draw_circle(((pos.0 - self.camera_pos.0) * self.scale.0, (pos.1 - self.camera_pos.1) * self.scale.1),
+                                         bot.get_size() * self.scale.0 + 4.0,
+                                         Color::from_rgb(0xFF, 0xFF, 0x40),
+                                         2.0);
+                }
             }
         }
 
+        core.set_target_bitmap(self.allegro_data.get_display().get_backbuffer());
 
+        core.draw_bitmap(&self.field_bmp, self.frame_pos.0, self.frame_pos.1, Flag::zero());
 
-        if self.camera_pos.0 < f32::EPSILON {
-            self.allegro_data.get_primitives_addon().draw_line(BORDER_THICKNESS_HALF, 0.0, BORDER_THICKNESS_HALF, self.frame_size.1, Color::from_rgb(0xFF, 0, 0), BORDER_THICKNESS);
-        }
-
-        if self.camera_pos.0 + self.camera_view_size.0 >= field_size.0 {
-            self.allegro_data.get_primitives_addon().draw_line(self.frame_size.0 - BORDER_THICKNESS_HALF, 0.0, self.frame_size.0 - BORDER_THICKNESS_HALF, self.frame_size.1, Color::from_rgb(0xFF, 0, 0), BORDER_THICKNESS);
-        }
-
-        if self.camera_pos.1 < f32::EPSILON {
-            self.allegro_data.get_primitives_addon().draw_line(0.0, BORDER_THICKNESS_HALF, self.frame_size.0, BORDER_THICKNESS_HALF, Color::from_rgb(0xFF, 0, 0), BORDER_THICKNESS);
-        }
+        if self.replay.is_none() {
+            self.graph_panel.draw(&self.allegro_data, self.simulator.get_statistics());
 
-        if self.camera_pos.1 + self.camera_view_size.1 >= field_size.1 {
-            self.allegro_data.get_primitives_addon().draw_line(0.0, self.frame_size.1 - BORDER_THICKNESS_HALF, self.frame_size.0, self.frame_size.1 - BORDER_THICKNESS_HALF, Color::from_rgb(0xFF, 0, 0), BORDER_THICKNESS);
+            //the inspector closes when the selected bot dies
+            if let Some(bot) = self.selected_bot.and_then(| id | self.simulator.get_bot(id)) {
+                self.inspector.draw(&self.allegro_data, &bot);
+            }
         }
 
-        core.set_target_bitmap(self.allegro_data.get_display().get_backbuffer());
-
-        core.draw_bitmap(&self.field_bmp, self.frame_pos.0, self.frame_pos.1, Flag::zero());
-
-        core.draw_text(self.allegro_data.get_std_font(), self.allegro_data.get_white(), 5.0, 5.0, FontAlign::Left, &format!("ticks: {}", self.simulator.get_ticks()));
+        let ticks = match self.replay {
+            Some(ref replay) => format!("replay tick: {}", replay.get_state().get_tick()),
+            None => format!("ticks: {}", self.simulator.get_ticks())
+        };
+        core.draw_text(self.allegro_data.get_std_font(), self.allegro_data.get_white(), 5.0, 5.0, FontAlign::Left, &ticks);
         core.draw_text(self.allegro_data.get_std_font(), self.allegro_data.get_white(), 5.0, 15.0, FontAlign::Left, &format!("tickrate: {}", self.tickrate));
+        //in replay mode the simulator is only a placeholder, so its seed says nothing about the replay
+        let origin = match self.replay {
+            Some(ref replay) => format!("recorded ticks: {} - {}", replay.get_first_tick(), replay.get_last_tick()),
+            None => format!("seed: {}", self.simulator.get_seed())
+        };
+        core.draw_text(self.allegro_data.get_std_font(), self.allegro_data.get_white(), 200.0, 5.0, FontAlign::Left, &origin);
+
+        if let (None, Some(statistics)) = (&self.replay, self.simulator.get_statistics().get_last()) {
+            core.draw_text(self.allegro_data.get_std_font(), self.allegro_data.get_white(), 400.0, 5.0, FontAlign::Left,
+                           &format!("bots: {} | births: {} | starved: {} | left field: {}",
+                                    statistics.population, statistics.births, statistics.deaths_starved, statistics.deaths_left_field));
+            core.draw_text(self.allegro_data.get_std_font(), self.allegro_data.get_white(), 400.0, 15.0, FontAlign::Left,
+                           &format!("energy avg: {:.1} max: {} | food eaten: {} | error: {}",
+                                    statistics.mean_energy, statistics.max_energy, statistics.food_eaten,
+                                    statistics.mean_error.map_or(String::from("-"), | e | format!("{:.2e}", e))));
+        }
 
 
         core.flip_display();